@@ -5,12 +5,19 @@ use pinocchio::{
     }, log::sol_log_slice, msg, program_error::ProgramError, ProgramResult
 };
 
-use bytemuck;
+use bytemuck::{
+    self, 
+    Zeroable
+};
 
 use crate::{
     config::Config,
+    constants::CONFIG_OPTION_CONDITION_ACCOUNT,
     state::{
-        condition::CommitCondition, 
+        condition::{
+            CommitCondition, 
+            UsageWindow
+        }, 
         error::WrapperError
     }, 
     utils::{
@@ -22,8 +29,10 @@ use crate::{
 pub struct ChangeCommit<'a, 'b>{
     pub signers:&'a[AccountInfo],
     pub commit_account:&'a AccountInfo,
-    pub config_data:RefMut<'a, Config>,
-    pub new_condition:&'b CommitCondition
+    pub config_data:RefMut<'a, [u8]>,
+    pub condition_account:Option<&'a AccountInfo>,
+    pub new_condition:&'b CommitCondition,
+    pub new_sub_conditions:&'b [CommitCondition]
 }
 
 impl<'a, 'b> TryFrom<(&'a [AccountInfo], &'b[u8])> for ChangeCommit<'a, 'b> {
@@ -32,13 +41,21 @@ impl<'a, 'b> TryFrom<(&'a [AccountInfo], &'b[u8])> for ChangeCommit<'a, 'b> {
     /// in that their corresponding PDAs sign for the transaction confirming to that
     /// commit form
     /// 
+    /// Condition account:- This is the account whose data the new commit condition would
+    /// read, it is optional and when passed it replaces the condition account of the commit,
+    /// it can only be passed when the commit stores a condition account key
+    /// 
     /// Commit account:- This is the account storing the commit configuration
     /// 
+    /// The instruction data holds the new condition followed by the sub-conditions
+    /// it combines if any(at most the sub-condition count of the commit), the
+    /// sub-conditions that are not passed are cleared
+    /// 
     fn try_from(value: (&'a [AccountInfo], &'b[u8])) -> Result<Self, Self::Error> {
 
         // Extract accounts & data
-        // [Signers] -- Commit account
-        let [signers@.., commit_account] = value.0 else{
+        // [Signers] -- [Condition account] -- Commit account
+        let [other_accounts@.., commit_account] = value.0 else{
                 return Err(ProgramError::NotEnoughAccountKeys);
         };
 
@@ -48,27 +65,47 @@ impl<'a, 'b> TryFrom<(&'a [AccountInfo], &'b[u8])> for ChangeCommit<'a, 'b> {
         is_program_account(commit_account, Config::LEN, &crate::ID)?;
 
         // Extract data
-        let new_condition = 
-            bytemuck::try_from_bytes::<CommitCondition>(&data).
+        // Condition -- [Sub-conditions]
+        let new_conditions = 
+            bytemuck::try_cast_slice::<u8, CommitCondition>(&data).
                 map_err(|_|ProgramError::InvalidInstructionData)?;
 
-        let mut config_data_ref = commit_account.try_borrow_mut_data().
-            map_err(|_| ProgramError::InvalidAccountData)?;
+        let [new_condition, new_sub_conditions@..] = new_conditions else{
+            return Err(ProgramError::InvalidInstructionData);
+        };
 
-        let config_data = 
-            bytemuck::try_from_bytes_mut::<Config>(&mut config_data_ref).
+        let config_data = commit_account.try_borrow_mut_data().
             map_err(|_| ProgramError::InvalidAccountData)?;
 
+        let loaded_config_data = Config::load(&config_data)?;
+
+        let config_base = &loaded_config_data.config.base;
+
+        if new_sub_conditions.len().gt(&usize::from(config_base.sub_condition_count[0])){
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
         // Check bounds constraints
-        let signer_account_count = u8::from_le_bytes(config_data.base.signer_count);
+        let signer_account_count = u8::from_le_bytes(config_base.form.signer_count);
 
-        if !signers.len().eq(&usize::from(signer_account_count)){
+        if other_accounts.len().lt(&usize::from(signer_account_count)){
             return Err(WrapperError::InvalidAccountKeysCount.into());
         };
 
+        let (signers, condition_account) = 
+            other_accounts.split_at(usize::from(signer_account_count));
+
+        // The condition account can only replace a stored key
+        let condition_account = match condition_account {
+            [] => None,
+            [condition_account] if config_base.has_option(CONFIG_OPTION_CONDITION_ACCOUNT) => 
+                Some(condition_account),
+            _ => return Err(WrapperError::InvalidAccountKeysCount.into()),
+        };
+
         // Check signers
         for (maybe_signer, expected_signer) in signers.iter().
-            zip(config_data.signer_keys.iter()){
+            zip(loaded_config_data.signer_keys.iter()){
             is_signer(maybe_signer)?;
 
             msg!("Maybe:- ");
@@ -82,18 +119,23 @@ impl<'a, 'b> TryFrom<(&'a [AccountInfo], &'b[u8])> for ChangeCommit<'a, 'b> {
             }
         }
 
-        // Check if the commit condition is valid
-        new_condition.is_valid()?;
+        // Check if the commit condition and the sub-conditions it combines are valid and
+        // the commit stores what they read
+        config_base.is_condition_valid(new_condition, new_sub_conditions)?;
 
-        // Should not panic since above conversion was successful
-        let config_data = RefMut::map(
-            config_data_ref, |config_data| bytemuck::from_bytes_mut(config_data));
+        // The condition account must be passed when the new condition reads it, otherwise
+        // the previous key would be kept
+        if new_condition.uses_condition_account_with(new_sub_conditions) && condition_account.is_none(){
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
 
         Ok(ChangeCommit{
             signers,
             commit_account,
             config_data,
-            new_condition
+            condition_account,
+            new_condition,
+            new_sub_conditions
         })
     }
 
@@ -106,7 +148,25 @@ impl<'a, 'b> ChangeCommit<'a, 'b>{
 /// Change the commit condition for the config
 pub fn process(&mut self) -> ProgramResult {
 
-    self.config_data.base.condition = *self.new_condition;
+    let config_data = Config::load_mut(&mut self.config_data)?;
+
+    config_data.config.base.condition = *self.new_condition;
+
+    // Clear the previous sub-conditions before setting the new ones
+    config_data.state.sub_conditions.fill(CommitCondition::zeroed());
+
+    config_data.state.sub_conditions[..self.new_sub_conditions.len()].
+        copy_from_slice(self.new_sub_conditions);
+
+    // The recent uses were recorded for the previous condition
+    if let Some(usage_window) = config_data.usage_window {
+        *usage_window = UsageWindow::zeroed();
+    }
+
+    if let (Some(condition_account), Some(condition_account_key)) = 
+        (self.condition_account, config_data.state.condition_account_key) {
+        *condition_account_key = *condition_account.key();
+    }
 
     Ok(())
 }
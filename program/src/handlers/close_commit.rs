@@ -6,10 +6,8 @@ use pinocchio::{
     ProgramResult,
 };
 
-use bytemuck;
-
 use crate::{
-    config::Config,
+    config::{Config, ConfigData},
     state::{
         error::WrapperError
     }, 
@@ -49,15 +47,13 @@ impl<'a, 'b> TryFrom<&'a [AccountInfo]> for CloseCommit<'a> {
         is_program_account(commit_account, Config::LEN, &crate::ID)?;
 
         // Extract the data
-        let mut config_data_ref = commit_account.try_borrow_mut_data().
+        let config_data_ref = commit_account.try_borrow_data().
             map_err(|_| ProgramError::InvalidAccountData)?;
 
-        let config_data = 
-            bytemuck::try_from_bytes_mut::<Config>(&mut config_data_ref).
-            map_err(|_| ProgramError::InvalidAccountData)?;
+        let ConfigData{ config, signer_keys, .. } = Config::load(&config_data_ref)?;
 
         // Check bounds constraints
-        let signer_account_count = u8::from_le_bytes(config_data.base.signer_count);
+        let signer_account_count = u8::from_le_bytes(config.base.form.signer_count);
 
         if signers.len().ne(&usize::from(signer_account_count)){
             return Err(WrapperError::InvalidAccountKeysCount.into());
@@ -65,9 +61,8 @@ impl<'a, 'b> TryFrom<&'a [AccountInfo]> for CloseCommit<'a> {
 
         // Check signers
         for (maybe_signer, expected_signer) in signers.iter().
-            zip(config_data.signer_keys.iter()){ // The number of signers provided here should be
-            is_signer(maybe_signer)?;    // <= MAX_SIGNERS, which is the no of public keys 
-                                                  // there is space allocated for in 'config_data.signer_keys',
+            zip(signer_keys.iter()){ // The number of signers provided here should be
+            is_signer(maybe_signer)?;    // the no of public keys stored in the commit account,
                                                   // this constraint is checked above
             if maybe_signer.key().ne(expected_signer){
                 return Err(ProgramError::MissingRequiredSignature);
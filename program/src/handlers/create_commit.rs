@@ -2,7 +2,7 @@ use pinocchio::{
     account_info::AccountInfo, instruction::{
         Seed, 
         Signer
-    }, log::{sol_log_slice}, msg, program_error::ProgramError, pubkey, sysvars::{
+    }, log::{sol_log_slice}, msg, program_error::ProgramError, pubkey::{self, Pubkey}, sysvars::{
         rent::Rent, 
         Sysvar
     }, ProgramResult
@@ -10,13 +10,16 @@ use pinocchio::{
 
 use bytemuck;
 
+extern crate alloc;
+
+use alloc::vec::Vec;
+
 use crate::{
     config::{
-        Config, ConfigBase
+        Config, ConfigBase, ConfigOptions, ConfigSections, ConfigState
     }, 
     constants::{
-        CONFIG_MAX_ACCOUNTS, COMMIT_SEEDS_LEN, CONFIG_MAX_SIGNERS, 
-        CONFIG_MIN_ACCOUNTS, HASH_LENGTH
+        COMMIT_SEEDS_LEN, CONFIG_MIN_ACCOUNTS, HASH_LENGTH
     }, 
     state::{
         error::WrapperError
@@ -34,11 +37,15 @@ pub struct CreateCommit<'a, 'b>{
     pub starter_account:&'a AccountInfo,
     pub commit_account:&'a AccountInfo,
     pub config_base_data:&'b ConfigBase,
+    pub config_options:ConfigOptions<'b>,
+    pub config_sections:ConfigSections<'b>,
+    pub config_data:&'b[u8],
 }
 
 impl<'a, 'b> TryFrom<(&'a [AccountInfo], &'b[u8])> for CreateCommit<'a, 'b> {
     /// Extract the commit data and accounts, checking the bounds contraints and signers
-    /// It expects the instruction data to contain the commit data only and the accounts to be
+    /// It expects the instruction data to contain the commit data only(the config base followed
+    /// by the state, options and sections it sets the lengths of) and the accounts to be
     /// in the following order:- [Accounts to commit to -- Signers] -- Starter account 
     /// -- System program -- Commit account
     /// [Accounts to commit]:-
@@ -76,26 +83,56 @@ impl<'a, 'b> TryFrom<(&'a [AccountInfo], &'b[u8])> for CreateCommit<'a, 'b> {
 
         sol_log_slice(data.len().to_le_bytes().as_ref());
 
+        if data.len().lt(&ConfigBase::LEN){
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Config base -- Config state -- Config options -- Config sections
+        let (config_base_data, config_data) = data.split_at(ConfigBase::LEN);
+
         let config_base_data = 
-            bytemuck::try_from_bytes::<ConfigBase>(&data).
+            bytemuck::try_from_bytes::<ConfigBase>(config_base_data).
+                map_err(|_|ProgramError::InvalidInstructionData)?;
+
+        // Check if the options are known, they set the parts of the state that follow
+        config_base_data.is_options_valid()?;
+
+        let (config_state, config_options_data) = 
+            ConfigState::try_from_bytes(config_base_data, config_data).
+                map_err(|_|ProgramError::InvalidInstructionData)?;
+
+        let (config_options, config_sections_data) = 
+            ConfigOptions::try_from_bytes(config_base_data, config_options_data).
                 map_err(|_|ProgramError::InvalidInstructionData)?;
 
+        let (config_sections, unused_data) = 
+            ConfigSections::try_from_bytes(&config_base_data.form, config_sections_data).
+                map_err(|_|ProgramError::InvalidInstructionData)?;
+
+        // The state, options and sections are copied as is so there should be nothing after them
+        if unused_data.len().ne(&0){
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
         // Check bounds constraints
-        let commit_account_count = u8::from_le_bytes(config_base_data.account_count);
+        let commit_account_count = u8::from_le_bytes(config_base_data.form.account_count);
+
+        let signer_account_count = u8::from_le_bytes(config_base_data.form.signer_count);
 
-        let signer_account_count = u8::from_le_bytes(config_base_data.signer_count);
+        // A forms or bundle commit only commits to the program signers, the forms are
+        // passed when invoking
+        let is_signers_only = config_base_data.is_forms_commit() || config_base_data.is_bundle_commit();
 
         msg!("-0");
 
-        if usize::from(commit_account_count).lt(&CONFIG_MIN_ACCOUNTS) || 
-            usize::from(commit_account_count).gt(&CONFIG_MAX_ACCOUNTS) {
+        if !is_signers_only && usize::from(commit_account_count).lt(&CONFIG_MIN_ACCOUNTS) {
             return Err(ProgramError::from(WrapperError::InvalidAccountCount));
         }
 
         msg!("-1");
 
-        if signer_account_count.eq(&0) || usize::from(signer_account_count).gt(&CONFIG_MAX_SIGNERS) || 
-            signer_account_count.ge(&commit_account_count){
+        if signer_account_count.eq(&0) || 
+            (!is_signers_only && signer_account_count.ge(&commit_account_count)){
             return Err(ProgramError::from(WrapperError::InvalidSignerCount));
         }
 
@@ -134,10 +171,47 @@ impl<'a, 'b> TryFrom<(&'a [AccountInfo], &'b[u8])> for CreateCommit<'a, 'b> {
 
         msg!("-3");
 
-        verify_signers(signers, program_signers, &config_base_data.signer_bumps)?;
+        verify_signers(signers, program_signers, config_sections.signer_bumps)?;
+
+        // Check if the commit condition and the sub-conditions it combines are valid
+        config_base_data.is_condition_valid(&config_base_data.condition, config_state.sub_conditions)?;
+
+        // Check if the form of a forms commit only commits to the program signers
+        config_base_data.is_forms_commit_valid(&config_sections)?;
+
+        // Check if the bundle of a bundle commit is valid and it's form only commits to the
+        // program signers
+        config_base_data.is_bundle_commit_valid(&config_options, &config_sections)?;
+
+        // Check if the committed byte ranges are valid
+        config_base_data.form.is_data_commit_valid(&config_sections)?;
+
+        // Check if the account sets are at valid positions
+        config_base_data.form.is_account_sets_valid(&config_sections)?;
 
-        // Check if the commit condition is valid
-        config_base_data.condition.is_valid()?;
+        // Check if the account owner constraints are at valid positions
+        config_base_data.form.is_account_owner_constraints_valid(&config_sections)?;
+
+        // Check if the PDA constraints are at valid positions and their seeds are valid
+        config_base_data.form.is_account_pda_constraints_valid(&config_sections)?;
+
+        // Check if the account data assertions are at committed positions and are valid
+        config_base_data.form.is_account_data_assertions_valid(&config_sections)?;
+
+        // Check if the account invariants are at committed positions and are valid
+        config_base_data.form.is_account_invariants_valid(&config_sections)?;
+
+        // Check if the committed account flags are valid
+        config_base_data.form.is_account_flags_valid(&config_sections)?;
+
+        // Check if the field constraints are within the committed instruction data
+        config_base_data.form.is_field_constraints_valid(&config_sections)?;
+
+        // Check if the value budget can be read from the committed instruction data
+        config_base_data.is_value_budget_valid(config_state.value_budget)?;
+
+        // Check if the lamport cap is only stored when it caps the outflow
+        config_options.is_max_lamports_out_valid()?;
 
         msg!("-4");
 
@@ -146,7 +220,10 @@ impl<'a, 'b> TryFrom<(&'a [AccountInfo], &'b[u8])> for CreateCommit<'a, 'b> {
             signers,
             starter_account,
             commit_account,
-            config_base_data
+            config_base_data,
+            config_options,
+            config_sections,
+            config_data
         })
     }
 
@@ -157,25 +234,29 @@ impl<'a, 'b> CreateCommit<'a, 'b>{
 /// Process the create commit instruction
 pub fn process(&self) -> ProgramResult {
 
-    // Get the commit accounts keys
-    let mut commit_accounts:[&[u8];CONFIG_MAX_ACCOUNTS] = [&[];CONFIG_MAX_ACCOUNTS];
-    
-    for (account, commit_account) in 
-        self.commit_accounts.iter().zip(commit_accounts.iter_mut()){
-            *commit_account = account.key().as_ref();
-        }
+    // Get the account indices followed by the commit accounts keys, the keys at the
+    // positions that are not pinned are skipped
+    let commit_accounts:Vec<&[u8]> = core::iter::once(self.config_sections.account_indices).
+        chain(self.commit_accounts.iter().enumerate().
+            filter(|(position, _)| !self.config_sections.is_unpinned_position(*position)).
+            map(|(_, account)| account.key().as_slice())).collect();
 
     msg!("-5");
     
     // Take the hash of the accounts to commit to.
     let mut commit_accounts_hash:[u8;HASH_LENGTH] = [0;HASH_LENGTH];
     
-    hashv(&commit_accounts[..usize::from(self.config_base_data.account_count[0])], 
-    &mut commit_accounts_hash);
+    hashv(&commit_accounts, &mut commit_accounts_hash);
+
+    // Take the hash of the byte ranges and constraints placed on the instruction
+    let mut constraints_hash:[u8;HASH_LENGTH] = [0;HASH_LENGTH];
+
+    self.config_base_data.get_constraints_hash(&self.config_options, &self.config_sections, &mut constraints_hash);
 
     // Get the seeds used to create the commit account
     let mut commit_seeds = self.config_base_data.
-        get_commit_seeds(commit_accounts_hash.as_ref(), None)?;
+        get_commit_seeds(&self.config_sections, commit_accounts_hash.as_ref(), 
+            constraints_hash.as_ref(), None)?;
 
     msg!("-6");
 
@@ -213,7 +294,10 @@ pub fn process(&self) -> ProgramResult {
 fn create_account(&self, commit_seeds:&[&[u8];COMMIT_SEEDS_LEN])->Result<(), ProgramError>{
     let rent = Rent::get()?;
 
-    let required_lamports = rent.minimum_balance(Config::LEN);
+    // The commit only pays for the state, options and sections it uses
+    let account_len = self.config_base_data.get_account_len(self.config_data.len());
+
+    let required_lamports = rent.minimum_balance(account_len);
 
     let commit_seeds:[Seed;COMMIT_SEEDS_LEN] = core::array::from_fn(
             |index| Seed::from(commit_seeds[index]));
@@ -224,7 +308,7 @@ fn create_account(&self, commit_seeds:&[&[u8];COMMIT_SEEDS_LEN])->Result<(), Pro
         from:self.starter_account,
         to:self.commit_account,
         lamports:required_lamports,
-        space:Config::LEN as u64,
+        space:account_len as u64,
         owner: &crate::ID
     }.invoke_signed(&[signer])?;
         
@@ -236,26 +320,45 @@ fn create_account(&self, commit_seeds:&[&[u8];COMMIT_SEEDS_LEN])->Result<(), Pro
 #[inline(always)] 
 fn write_commit_account_data(&self)->Result<(), ProgramError>{
 
-    // Extract the config data
-    let config_data = unsafe{
+    let commit_account_data = unsafe{
         // SAFETY: No account data besides this one is borrowed during the call of this instruction
         // as an additional guarantee the commit account is guaranteed to be unique due to the nature
         // of it's construction
-        let commit_account_data = self.commit_account.borrow_mut_data_unchecked();
-
-        bytemuck::try_from_bytes_mut::<Config>(commit_account_data).map_err(|_| ProgramError::InvalidAccountData)?
+        self.commit_account.borrow_mut_data_unchecked()
     };
 
+    // Config -- Config state -- Config options -- Config sections -- [Signer keys] -- 
+    // [Form usage] -- [Usage window]
+    let (config, other_data) = commit_account_data.split_at_mut(Config::LEN);
+
+    let (config_data, other_data) = 
+        other_data.split_at_mut(self.config_data.len());
+
+    // The usage table of a forms commit and the usage window start out zeroed
+    let (signer_keys_data, _) = 
+        other_data.split_at_mut(self.signers.len() * core::mem::size_of::<Pubkey>());
+
+    // Extract the config
+    let config = bytemuck::try_from_bytes_mut::<Config>(config).
+        map_err(|_| ProgramError::InvalidAccountData)?;
+
     // Set the base info
-    config_data.base = *self.config_base_data;
+    config.base = *self.config_base_data;
 
     // Set the key that would be able to initiate the transaction
-    config_data.starter_key = *self.starter_account.key();
+    config.starter_key = *self.starter_account.key();
+
+    // Set the state, options and sections, they were checked against the counts and
+    // options in the base
+    config_data.copy_from_slice(self.config_data);
 
     // Set all the signers, they would be used for making changes to the commit
     // and they are also used to derive the PDAs
+    let signer_keys = bytemuck::try_cast_slice_mut::<u8, Pubkey>(signer_keys_data).
+        map_err(|_| ProgramError::InvalidAccountData)?;
+
     for (signer_key, config_signer_key) in self.signers.iter().
-        map(|account|account.key()).zip(config_data.signer_keys.iter_mut()){
+        map(|account|account.key()).zip(signer_keys.iter_mut()){
         *config_signer_key = *signer_key;
     }
 
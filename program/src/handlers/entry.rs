@@ -1,4 +1,3 @@
-use arrayvec::ArrayVec;
 use pinocchio::{
     ProgramResult, account_info::{
         AccountInfo, 
@@ -10,14 +9,23 @@ use pinocchio::{
         Instruction, 
         Seed, 
         Signer
-    }, log::sol_log_slice, msg, program_error::ProgramError, pubkey
+    }, log::sol_log_slice, msg, program_error::ProgramError, pubkey::{self, Pubkey}, sysvars::{
+        Sysvar, 
+        clock::Clock
+    }
 };
 
 use crate::{
-    config::Config, 
+    WrapperError,
+    condition::ConditionContext,
+    config::{
+        CommitForm, Config, ConfigData, ConfigDataMut, ConfigSections
+    }, 
     constants::{
-        CONFIG_MAX_ACCOUNTS, CONFIG_MAX_SIGNERS, HASH_LENGTH, MAX_INSTRUCTION_ACCOUNTS
+        COMMIT_SEEDS_LEN, HASH_LENGTH
     }, 
+    bundle::{BundleInstructionData, get_bundle_hash},
+    form::{FormData, FormLeafData},
     utils::{
         hashv, is_program_account, is_signer
     }
@@ -25,39 +33,60 @@ use crate::{
 
 extern crate alloc;
 
-use alloc::string::ToString;
+use alloc::{
+    string::ToString, 
+    vec::Vec
+};
 
 /// Stores the state for the entry instruction
 pub struct Entry<'a, 'b>{
-    program_accounts:&'a[AccountInfo],
     commit_account:&'a AccountInfo,
+    instructions:Vec<EntryInstruction<'a, 'b>>,
+    form_leaf:Option<FormLeafData<'b>>,
+    config_data:RefMut<'a, [u8]>
+}
+
+/// Stores an instruction executed by the entry instruction, the form is the one passed
+/// in the instruction data for a forms or bundle commit, otherwise it is not set and the
+/// form of the commit account is used
+struct EntryInstruction<'a, 'b>{
+    program_accounts:&'a[AccountInfo],
     program_data:&'b[u8], 
-    config_data:RefMut<'a, Config>
+    account_set_proofs:&'b[u8],
+    form:Option<FormData<'b>>
 }
 
 impl<'a, 'b> TryFrom<(&'a[AccountInfo], &'b[u8])> for Entry<'a, 'b> {
     /// Extract the accounts and check the signer account signed and the commit account is as expected
-    /// [Accounts used by the program] -- Starter account -- Commit account
-    /// [Accounts used by the program]:- These are accounts that the executing program would use
+    /// [Accounts used by the program] -- [Condition account] -- Starter account -- Commit account
+    /// [Accounts used by the program]:- These are accounts that the executing program would use, for a
+    /// bundle commit these are the accounts of each instruction one after another
+    /// 
+    /// Condition account:- This is the account whose data the commit condition reads, it is only
+    /// passed when the commit condition requires it
     /// 
     /// Starter account:- This is the account that is required to sign 
     /// before this commit can be called
     /// 
     /// Commit account:- This is the account that stores the configuration of the commit
     /// 
+    /// The instruction data holds the data of the program followed by the proofs of the
+    /// `Merkle` account sets if any, in the order of the account sets, for a forms commit
+    /// the form leaf used(see FormLeafData::try_split_from) comes at the end, for a bundle
+    /// commit it holds each instruction one after another(see BundleInstructionData::try_split_from)
+    /// 
     fn try_from(value: (&'a[AccountInfo], &'b[u8])) -> Result<Self, Self::Error> {
 
         msg!("-0");
 
         // Extract accounts
-        // [Accounts used by the program] -- Starter account -- Commit account
+        // [Accounts used by the program] -- [Condition account] -- Starter account -- Commit account
         let [program_accounts@.., starter_account, 
             commit_account] = value.0 else{
                 return Err(ProgramError::NotEnoughAccountKeys);
         };
 
-        // Extract data(no metadata is added to the instruction data)
-        let program_data = value.1;
+        let data = value.1;
 
         msg!("-1");
 
@@ -70,50 +99,104 @@ impl<'a, 'b> TryFrom<(&'a[AccountInfo], &'b[u8])> for Entry<'a, 'b> {
         let mut data_ref = commit_account.try_borrow_mut_data()?;
 
         // Extract config data
-        let config_data = 
-        bytemuck::try_from_bytes_mut::<Config>(&mut data_ref).
-        map_err(|_| ProgramError::InvalidAccountData)?;
+        let ConfigDataMut{ config, state, options, sections:config_sections, form_usage, 
+            usage_window, .. } = Config::load_mut(&mut data_ref)?;
 
         msg!("-3");
 
         // Check if the starter signed and is as expected
         is_signer(starter_account)?;
 
-        if starter_account.key().ne(&config_data.starter_key){
+        if starter_account.key().ne(&config.starter_key){
             return Err(ProgramError::MissingRequiredSignature);
         }
 
         msg!("-4");
-        
-        // Check if instruction data matches the form committed to
-        if !config_data.base.length_matches_commit_type(program_data.len()){
-            return Err(ProgramError::InvalidInstructionData);
+
+        // Extract the condition account if the commit condition requires it
+        let (program_accounts, condition_account) = if config.base.uses_condition_account(state.sub_conditions){
+            let [program_accounts@.., condition_account] = program_accounts else{
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+
+            if state.condition_account_key.is_none_or(|condition_account_key| 
+                condition_account.key().ne(condition_account_key)){
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            (program_accounts, Some(condition_account))
         }
+        else{
+            (program_accounts, None)
+        };
+
+        // Extract the instructions to execute along with the form leaf for a forms commit
+        let (instructions, form_leaf) = if config.base.is_bundle_commit(){
+            let instructions = Self::get_bundle_instructions(program_accounts, data, 
+                usize::from(config.base.bundle_length[0]), &config_sections)?;
+
+            (instructions, None)
+        }
+        else if config.base.is_forms_commit(){
+            // [Rest of the data] -- Form leaf
+            let (form_leaf, data) = FormLeafData::try_split_from(data)?;
+
+            // The program signers are those of the commit account
+            if !form_leaf.form.has_signers_of(&config_sections){
+                return Err(WrapperError::InvalidFormLeaf.into());
+            }
+
+            // Check if the leaf can still be used and it's condition is met, and update
+            // it's usage if so
+            let context = ConditionContext{
+                clock: Clock::get()?,
+                condition_account: None
+            };
+
+            form_leaf.update_usage(form_usage, &context)?;
+
+            let instruction = EntryInstruction::try_from_data(program_accounts, data, 
+                Some(form_leaf.form), &config_sections)?;
+
+            (alloc::vec![instruction], Some(form_leaf))
+        }
+        else{
+            let instruction = EntryInstruction::try_from_data(program_accounts, data, 
+                None, &config_sections)?;
+
+            (alloc::vec![instruction], None)
+        };
 
         msg!("-5");
-        
-        // Check if the keys are sufficient
-        if usize::from(config_data.base.account_count[0]).gt(&program_accounts.len()) {
-            return Err(ProgramError::NotEnoughAccountKeys);
+
+        // Check if the data and accounts of each instruction match it's form
+        for instruction in instructions.iter(){
+            instruction.check_form(&config.base.form, &config_sections)?;
         }
 
         msg!("-6");        
 
         // Check if the commit condition is valid, and update it if so
-        config_data.base.update_condition()?;
+        config.base.update_condition(state.sub_conditions, usage_window, condition_account)?;
+
+        // Check if the value moved by this call is within the budget, and update it if so, the
+        // amounts of all the instructions in the budget mask of a bundle commit are spent
+        if let Some(value_budget) = state.value_budget {
+            for (index, instruction) in instructions.iter().enumerate(){
+                if options.is_budgeted_instruction(index){
+                    value_budget.update(instruction.program_data)?;
+                }
+            }
+        }
 
         msg!("-7");
 
-        // Should not panic since above conversion was successful
-        let config_data_ref = RefMut::map(
-            data_ref, |old_data_ref| bytemuck::from_bytes_mut::<Config>(old_data_ref));
-
         Ok(
             Entry{ 
-                program_accounts, 
                 commit_account, 
-                config_data:config_data_ref, 
-                program_data 
+                config_data:data_ref, 
+                instructions,
+                form_leaf
             }
         )
     }
@@ -121,24 +204,90 @@ impl<'a, 'b> TryFrom<(&'a[AccountInfo], &'b[u8])> for Entry<'a, 'b> {
     type Error = ProgramError;
 }
 
+impl<'a, 'b> EntryInstruction<'a, 'b>{
+
+    /// Splits the proofs of the `Merkle` account sets of the form off the end of the data
+    /// Program data -- [Account set proofs]
+    fn try_from_data(program_accounts:&'a[AccountInfo], data:&'b[u8], form:Option<FormData<'b>>, 
+        config_sections:&ConfigSections)->Result<Self, ProgramError>{
+        let account_set_proofs_len = match &form {
+            Some(form) => form.sections.get_account_set_proofs_len(),
+            None => config_sections.get_account_set_proofs_len()
+        };
+
+        if data.len().lt(&account_set_proofs_len){
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let (program_data, account_set_proofs) = data.split_at(data.len() - account_set_proofs_len);
+
+        Ok(EntryInstruction{ program_accounts, program_data, account_set_proofs, form })
+    }
+
+    /// Gets the form the instruction is checked against, the form passed in the instruction
+    /// data if any, otherwise that of the commit account
+    #[inline(always)]
+    fn get_form<'c>(&'c self, config_form:&'c CommitForm, 
+        config_sections:&'c ConfigSections<'c>)->(&'c CommitForm, &'c ConfigSections<'c>){
+        match &self.form {
+            Some(form) => (form.form, &form.sections),
+            None => (config_form, config_sections)
+        }
+    }
+
+    /// Checks if the data and accounts of the instruction match it's form, the checks that
+    /// do not need the constraints hash are made here
+    fn check_form(&self, config_form:&CommitForm, config_sections:&ConfigSections)->Result<(), ProgramError>{
+        let (form, sections) = self.get_form(config_form, config_sections);
+
+        // Check if instruction data matches the form committed to
+        if !form.length_matches_commit_type(self.program_data.len()){
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Check if the fields of the instruction data are within their bounds
+        sections.check_field_constraints(self.program_data)?;
+
+        // Check if the keys are sufficient, the program account is always passed
+        if self.program_accounts.is_empty() || 
+            usize::from(form.account_count[0]).gt(&self.program_accounts.len()) {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        // Check if the writable and signer flags of the accounts are as committed to
+        sections.check_account_flags(form.other_account_flags[0], self.program_accounts)?;
+
+        Ok(())
+    }
+}
+
 impl<'a, 'b> Entry<'a, 'b>{
 
 // This function would only ever be called once, it is separated for readability
 /// This function is for getting all the individual accounts that are commited to
 /// in the commit account, it uses the indices stored in the config data account
-/// to know where to find them and performs a bounds check for each index
+/// to know where to find them and performs a bounds check for each index, the
+/// indices come first since they are hashed along with the accounts
 #[inline(always)] 
-fn get_commit_accounts(program_accounts:&'a[AccountInfo], config_data:&Config)-> Result<[&'a [u8];CONFIG_MAX_ACCOUNTS], ProgramError>{
-    let mut commit_accounts:[&[u8];CONFIG_MAX_ACCOUNTS] = [&[];CONFIG_MAX_ACCOUNTS];
+fn get_commit_accounts<'c>(program_accounts:&'c[AccountInfo], config_sections:&ConfigSections<'c>)-> Result<Vec<&'c [u8]>, ProgramError>
+{
+    let mut commit_accounts:Vec<&[u8]> = Vec::with_capacity(config_sections.account_indices.len() + 1);
 
+    commit_accounts.push(config_sections.account_indices);
     
-    for (index, commit_account) in config_data.base.account_indices.iter().
-    take(usize::from(u8::from_le_bytes(config_data.base.account_count))).zip(commit_accounts.iter_mut()){
+    for (position, index) in config_sections.account_indices.iter().enumerate(){
+        // The accounts at positions that are not pinned are checked separately
+        if config_sections.is_unpinned_position(position){
+            continue;
+        }
+
         let index = usize::from(*index);
 
-        *commit_account = program_accounts.get(index).
+        let commit_account = program_accounts.get(index).
             ok_or(ProgramError::NotEnoughAccountKeys)?.key();
 
+        commit_accounts.push(commit_account);
+
         let length = &program_accounts.len();
 
         msg!(length.to_string().as_str());
@@ -157,7 +306,7 @@ fn get_commit_accounts(program_accounts:&'a[AccountInfo], config_data:&Config)->
 /// This function is used to get the accounts metas that would be used for
 /// invoking the program
 #[inline(always)] 
-fn get_account_metas(program_accounts:&[AccountInfo])->ArrayVec<AccountMeta,MAX_INSTRUCTION_ACCOUNTS>{
+fn get_account_metas(program_accounts:&[AccountInfo])->Vec<AccountMeta>{
         program_accounts.iter().
         take(program_accounts.len() - 1). // Exclude the program account
         map(|account|
@@ -174,32 +323,161 @@ fn get_program_account(program_accounts:&[AccountInfo])->&AccountInfo{
     &program_accounts[program_accounts.len() - 1] // Program account is always added to the back
 }
 
-/// This function performs the necessary checks and then invokes the program
+// This function would only ever be called once, it is separated for readability
+/// This function is used to get the instructions of a bundle commit, they are extracted
+/// from the start of the data one after another, each using the next account count
+/// accounts, all the data and accounts must be used
+fn get_bundle_instructions(program_accounts:&'a[AccountInfo], data:&'b[u8], bundle_length:usize, 
+    config_sections:&ConfigSections)->Result<Vec<EntryInstruction<'a, 'b>>, ProgramError>{
+    let mut instructions:Vec<EntryInstruction> = Vec::with_capacity(bundle_length);
+
+    let (mut program_accounts, mut data) = (program_accounts, data);
+
+    for _ in 0..bundle_length{
+        let (bundle_instruction, rest_data) = BundleInstructionData::try_split_from(data)?;
+
+        // The program signers are those of the commit account
+        if !bundle_instruction.form.has_signers_of(config_sections){
+            return Err(WrapperError::InvalidBundleInstruction.into());
+        }
+
+        let account_count = usize::from(bundle_instruction.instruction.account_count);
+
+        if program_accounts.len().lt(&account_count){
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let (instruction_accounts, rest_accounts) = program_accounts.split_at(account_count);
+
+        instructions.push(EntryInstruction{
+            program_accounts:instruction_accounts,
+            program_data:bundle_instruction.program_data,
+            account_set_proofs:bundle_instruction.account_set_proofs,
+            form:Some(bundle_instruction.form)
+        });
+
+        (program_accounts, data) = (rest_accounts, rest_data);
+    }
+
+    if program_accounts.len().ne(&0) || data.len().ne(&0){
+        return Err(WrapperError::InvalidBundleInstruction.into());
+    }
+
+    Ok(instructions)
+}
+
+// This function is only ever called once and is separated for readability
+/// This function is used to get the program signers passed to the instructions, they
+/// are derived from the signer keys and bumps, a program signer that is not passed can
+/// not lose lamports so it is left out
+fn get_program_signers(&self, signer_keys:&[Pubkey], signer_bumps:&[u8])->Result<Vec<&'a AccountInfo>, ProgramError>{
+    let mut program_signers:Vec<&AccountInfo> = Vec::with_capacity(signer_keys.len());
+
+    for (signer_key, signer_bump) in signer_keys.iter().zip(signer_bumps.iter()){
+        let program_signer_key = pubkey::create_program_address(
+            &[signer_key.as_ref(), core::slice::from_ref(signer_bump)], &crate::ID).
+                map_err(|_| WrapperError::InvalidSignerSeeds)?;
+
+        let program_signer = self.instructions.iter().
+            flat_map(|instruction| instruction.program_accounts.iter()).
+            find(|account| account.key().eq(&program_signer_key));
+
+        if let Some(program_signer) = program_signer{
+            program_signers.push(program_signer);
+        }
+    }
+
+    Ok(program_signers)
+}
+
+/// Get the total lamports of the accounts
+#[inline(always)]
+fn get_total_lamports(accounts:&[&AccountInfo])->Result<u64, ProgramError>{
+    accounts.iter().try_fold(0u64, |total, account| 
+        total.checked_add(account.lamports()).ok_or(ProgramError::ArithmeticOverflow))
+}
+
+/// This function performs the necessary checks and then invokes the program, the
+/// instructions of a bundle commit are invoked in order
 pub fn process(&self)->ProgramResult{
 
-    let commit_accounts = 
-        Self::get_commit_accounts(self.program_accounts, 
-            &self.config_data)?; // Get the accounts that were committed to.
-    
-    // Zero init could be avoided here
-    let mut commit_accounts_hash:[u8;HASH_LENGTH] = [0;HASH_LENGTH];
-    
-    hashv(&commit_accounts[..usize::from(u8::from_le_bytes(self.config_data.base.account_count))], 
-    &mut commit_accounts_hash);
+    let ConfigData{ config, options, sections:config_sections, signer_keys, .. } = 
+        Config::load(&self.config_data)?;
+
+    let mut instruction_hashes:Vec<[u8;HASH_LENGTH]> = Vec::with_capacity(self.instructions.len());
+
+    for instruction in self.instructions.iter(){
+        let (form, sections) = instruction.get_form(&config.base.form, &config_sections);
+
+        // Check if the accounts at the positions bound to account sets are members of them
+        sections.check_account_sets(instruction.program_accounts, instruction.account_set_proofs)?;
+
+        // Check if the accounts at the positions bound to owner constraints meet them
+        sections.check_account_owner_constraints(instruction.program_accounts)?;
 
-    msg!("-9");
+        // Check if the accounts at the positions bound to PDA constraints are derived as committed to
+        sections.check_account_pda_constraints(instruction.program_accounts, instruction.program_data)?;
 
-    let commit_seeds =   
-     self.config_data.base.get_commit_seeds(&commit_accounts_hash, // Use the necessary information
-             Some(self.program_data))?; // to construct the commit key
+        let commit_accounts = 
+            Self::get_commit_accounts(instruction.program_accounts, 
+                sections)?; // Get the accounts that were committed to.
+        
+        // Zero init could be avoided here
+        let mut commit_accounts_hash:[u8;HASH_LENGTH] = [0;HASH_LENGTH];
+        
+        hashv(&commit_accounts, &mut commit_accounts_hash);
+
+        msg!("-9");
 
-     // Check commit PDA
-    let expected_commit_account = pubkey::create_program_address(commit_seeds.as_ref(), 
-        &crate::ID)
-            .map_err(|_| ProgramError::InvalidSeeds)?;
+        let mut constraints_hash:[u8;HASH_LENGTH] = [0;HASH_LENGTH];
+
+        match &instruction.form {
+            Some(_) => form.get_constraints_hash(sections, &mut constraints_hash),
+            None => config.base.get_constraints_hash(&options, sections, &mut constraints_hash)
+        }
 
-    if !self.commit_account.key().eq(&expected_commit_account) { 
-        return Err(ProgramError::InvalidSeeds);    
+        let commit_seeds =   
+         form.get_commit_seeds(sections, &commit_accounts_hash, // Use the necessary information
+                 &constraints_hash, &config.base.commit_bump, Some(instruction.program_data))?; // to construct the commit key
+
+        // The bump is not part of the forms passed in the instruction data
+        let form_seeds = &commit_seeds[..COMMIT_SEEDS_LEN - 1];
+
+        if let Some(form_leaf) = &self.form_leaf {
+            // Check the leaf is one of the forms
+            if !options.form_root.is_some_and(|form_root| form_leaf.is_leaf_of(form_seeds, form_root)){
+                return Err(WrapperError::InvalidFormLeaf.into());
+            }
+        }
+        else if config.base.is_bundle_commit(){
+            // The bundle is checked once all the instructions are hashed
+            let mut instruction_hash:[u8;HASH_LENGTH] = [0;HASH_LENGTH];
+
+            hashv(form_seeds, &mut instruction_hash);
+
+            instruction_hashes.push(instruction_hash);
+        }
+        else{
+            // Check commit PDA
+            let expected_commit_account = pubkey::create_program_address(commit_seeds.as_ref(), 
+                &crate::ID)
+                    .map_err(|_| ProgramError::InvalidSeeds)?;
+
+            if !self.commit_account.key().eq(&expected_commit_account) { 
+                return Err(ProgramError::InvalidSeeds);    
+            }
+        }
+    }
+
+    // Check the instructions are those of the bundle and in it's order
+    if config.base.is_bundle_commit(){
+        let mut bundle_hash:[u8;HASH_LENGTH] = [0;HASH_LENGTH];
+
+        get_bundle_hash(&instruction_hashes, &mut bundle_hash);
+
+        if options.bundle.is_none_or(|bundle| bundle_hash.ne(&bundle.hash)){
+            return Err(WrapperError::InvalidBundleInstruction.into());
+        }
     }
 
     // At this point we can execute the transaction because, the transaction form conforms to what was
@@ -207,34 +485,65 @@ pub fn process(&self)->ProgramResult{
 
     msg!("-10");
 
-    // Build the instruction
-    let instruction = Instruction {
-         program_id: Self::get_program_account(self.program_accounts).key(), 
-         data: self.program_data, 
-         accounts:&Self::get_account_metas(self.program_accounts)
-        };
+    // Build the signers
+    let seeds:Vec<[Seed;2]> = signer_keys.iter().zip(config_sections.signer_bumps.iter()).
+        map(|(signer_key, signer_bump)| [Seed::from(signer_key.as_ref()), 
+        Seed::from(core::slice::from_ref(signer_bump))]).collect();
+
+    let signers:Vec<Signer> = seeds.iter().
+        map(Signer::from).collect();
 
     msg!("-11");
 
-    // Build the account infos
-    let accounts_ref:ArrayVec<&AccountInfo,MAX_INSTRUCTION_ACCOUNTS> = self.program_accounts.iter().
-        map(|account_info| account_info).collect();
+    // Take the lamports of the program signers before any instruction is invoked, they are
+    // only needed when the outflow is capped
+    let program_signers = match options.max_lamports_out {
+        Some(_) => self.get_program_signers(signer_keys, config_sections.signer_bumps)?,
+        None => Vec::new()
+    };
 
-    msg!("-12");
+    let lamports_before = Self::get_total_lamports(&program_signers)?;
 
-    // Build the signers
-    let seeds:[[Seed;2];CONFIG_MAX_SIGNERS] = core::array::from_fn
-        (|index| [Seed::from(self.config_data.signer_keys[index].as_ref()), 
-        Seed::from(&self.config_data.base.signer_bumps[index..(index + 1)])]);
+    for instruction in self.instructions.iter(){
+        let (_, sections) = instruction.get_form(&config.base.form, &config_sections);
+
+        // Check if the data of the accounts holds the assertions right before the instruction
+        // is invoked, so the earlier instructions of a bundle are taken into account
+        sections.check_account_data_assertions(instruction.program_accounts)?;
+
+        // Build the instruction
+        let program_instruction = Instruction {
+             program_id: Self::get_program_account(instruction.program_accounts).key(), 
+             data: instruction.program_data, 
+             accounts:&Self::get_account_metas(instruction.program_accounts)
+            };
 
-    let signers:[Signer;CONFIG_MAX_SIGNERS] = core::array::
-        from_fn(|index|Signer::from(&seeds[index]));
+        msg!("-12");
 
-    msg!("-13");
+        // Build the account infos
+        let accounts_ref:Vec<&AccountInfo> = instruction.program_accounts.iter().
+            map(|account_info| account_info).collect();
 
-    // Invoke the main program with the provided instruction.
-    slice_invoke_signed(&instruction,  accounts_ref.as_slice(), 
-        signers[..usize::from(u8::from_le_bytes(self.config_data.base.signer_count))].as_ref())?;
+        msg!("-13");
+
+        // Take the states the invariants read before the instruction is invoked
+        let invariant_states = sections.get_account_invariant_states(instruction.program_accounts)?;
+
+        // Invoke the program with the provided instruction.
+        slice_invoke_signed(&program_instruction,  accounts_ref.as_slice(), signers.as_slice())?;
+
+        // Check if the instruction left the states the invariants read unchanged
+        sections.check_account_invariants(instruction.program_accounts, &invariant_states)?;
+    }
+
+    // Check if the net decrease of the lamports of the program signers is within the cap
+    if let Some(max_lamports_out) = options.max_lamports_out {
+        let lamports_after = Self::get_total_lamports(&program_signers)?;
+
+        if lamports_before.saturating_sub(lamports_after).gt(&u64::from_le_bytes(*max_lamports_out)){
+            return Err(WrapperError::LamportOutflowExceeded.into());
+        }
+    }
 
     msg!("-14");
     
@@ -14,4 +14,7 @@ pub mod withdraw_token;
 pub use withdraw_token::*;
 
 pub mod close_commit;
-pub use close_commit::*;
\ No newline at end of file
+pub use close_commit::*;
+
+pub mod reclaim_commit;
+pub use reclaim_commit::*;
\ No newline at end of file
@@ -0,0 +1,82 @@
+use pinocchio::{
+    account_info::{
+        AccountInfo
+    },
+    program_error::ProgramError,
+    ProgramResult,
+};
+
+use crate::{
+    config::{Config, ConfigData},
+    state::{
+        error::WrapperError
+    },
+    utils::{
+        is_program_account
+    }
+};
+
+/// Stores state for the reclaim commit instruction
+pub struct ReclaimCommit<'a>{
+    pub commit_account:&'a AccountInfo,
+    pub recipient_account:&'a AccountInfo
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ReclaimCommit<'a> {
+
+    /// Extracts the accounts and checks that the commit can never be used again,
+    /// anyone can call this instruction
+    ///
+    /// Recipient account:- This is the account that would be recieving the rent of the commit account,
+    /// it must be the first signer bound to the commit account
+    ///
+    /// Commit account:- This is the account storing the commit configuration
+    ///
+    fn try_from(value: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        // Extract the accounts
+
+        // Recipient account -- Commit account
+        let [recipient_account, commit_account] = value else{
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+
+        // Check if the account belongs to the program
+        is_program_account(commit_account, Config::LEN, &crate::ID)?;
+
+        // Extract the data
+        let config_data_ref = commit_account.try_borrow_data().
+            map_err(|_| ProgramError::InvalidAccountData)?;
+
+        let ConfigData{ config, state, signer_keys, .. } = Config::load(&config_data_ref)?;
+
+        // The rent goes back to the first signer rather than the caller
+        if signer_keys.first().is_none_or(|signer_key| recipient_account.key().ne(signer_key)){
+            return Err(WrapperError::InvalidRecipient.into());
+        }
+
+        // Check if the commit can never be used again
+        if !config.base.is_condition_expired(state.sub_conditions)?{
+            return Err(WrapperError::CommitNotExpired.into());
+        }
+
+        Ok(ReclaimCommit{
+            recipient_account,
+            commit_account
+        })
+    }
+
+    type Error = ProgramError;
+}
+
+impl<'a> ReclaimCommit<'a>{
+#[inline(always)]
+/// This closes a commit account whose condition can never be met again
+pub fn process(&self) -> ProgramResult {
+
+    *self.recipient_account.try_borrow_mut_lamports()? += self.commit_account.lamports();
+
+    // Sets the data, owner and lamports to zero
+    self.commit_account.close()
+}
+
+}
@@ -6,7 +6,9 @@ use pinocchio::{
     account_info::AccountInfo, memory::sol_memcmp, msg, program_error::ProgramError, pubkey::{self, Pubkey}, syscalls, ProgramResult
 };
 
-use crate::WrapperError;
+use crate::{
+    WrapperError, constants::{HASH_LENGTH, MERKLE_LEAF_PREFIX, MERKLE_NODE_PREFIX}
+};
 
 // To be called after the accounts bound has been checked.
 pub fn extract_program_signers<'a,'b>(accounts:&'a[AccountInfo], signers_count: u8, accounts_count: u8) -> &'a[AccountInfo]{
@@ -93,8 +95,9 @@ pub fn are_keys_equal(first:&Pubkey, second:&Pubkey)->bool{
     }
 }
 
+/// Checks if the account is initialized, owned by the owner and has at least the minimum data length
 #[inline(always)]
-pub fn is_program_account(account:&AccountInfo, data_len:usize, owner:&Pubkey)->Result<(), ProgramError>{
+pub fn is_program_account(account:&AccountInfo, min_data_len:usize, owner:&Pubkey)->Result<(), ProgramError>{
 
     if account.lamports().eq(&0){
         return Err(ProgramError::UninitializedAccount);
@@ -103,7 +106,7 @@ pub fn is_program_account(account:&AccountInfo, data_len:usize, owner:&Pubkey)->
     msg!("Data length:- ");
     msg!(account.data_len().to_string().as_str());
 
-    if account.data_len().ne(&data_len){
+    if account.data_len().lt(&min_data_len){
         return Err(ProgramError::InvalidAccountData);
     }
 
@@ -143,4 +146,33 @@ pub fn hashv(values:&[&[u8]], hash_result:&mut [u8; 32]){
                 hash_result as *mut _ as *mut u8,
             );
         }
+}
+
+/// Checks if the leaf is part of the tree with the given root, the proof is the hashes of the
+/// siblings from the leaf up, each pair is sorted before it is hashed so no path is needed and
+/// the leaves and nodes are hashed with different prefixes
+pub fn verify_merkle_proof(leaf:&[u8], proof:&[u8], root:&[u8; HASH_LENGTH])->bool{
+    if !proof.len().is_multiple_of(HASH_LENGTH){
+        return false;
+    }
+
+    let mut node:[u8;HASH_LENGTH] = [0;HASH_LENGTH];
+
+    hashv(&[&[MERKLE_LEAF_PREFIX], leaf], &mut node);
+
+    for sibling in proof.chunks_exact(HASH_LENGTH){
+        let (first, second) = if node.as_slice().le(sibling) {
+            (node.as_slice(), sibling)
+        } else {
+            (sibling, node.as_slice())
+        };
+
+        let mut parent:[u8;HASH_LENGTH] = [0;HASH_LENGTH];
+
+        hashv(&[&[MERKLE_NODE_PREFIX], first, second], &mut parent);
+
+        node = parent;
+    }
+
+    node.eq(root)
 }
\ No newline at end of file
@@ -29,7 +29,8 @@ pub enum InstructionTag{
     ChangeCommit,
     WithdrawNative,
     WithdrawToken,
-    CloseCommit
+    CloseCommit,
+    ReclaimCommit
 }
 
 impl TryFrom<u8> for InstructionTag{
@@ -41,6 +42,7 @@ impl TryFrom<u8> for InstructionTag{
             4 => Ok(InstructionTag::WithdrawNative),
             5 => Ok(InstructionTag::WithdrawToken),
             6 => Ok(InstructionTag::CloseCommit),
+            7 => Ok(InstructionTag::ReclaimCommit),
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
@@ -93,6 +95,11 @@ fn process_instruction(
             let close_commit = CloseCommit::try_from(accounts)?;
 
             close_commit.process()
+        },
+        InstructionTag::ReclaimCommit=>{
+            let reclaim_commit = ReclaimCommit::try_from(accounts)?;
+
+            reclaim_commit.process()
         }
     }
 }
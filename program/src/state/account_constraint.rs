@@ -0,0 +1,434 @@
+use bytemuck::{
+    Pod,
+    Zeroable
+};
+
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey
+};
+
+use crate::{
+    WrapperError, condition::Comparison, constants::{ACCOUNT_CHECK_DATA_LENGTH, ACCOUNT_CHECK_DISCRIMINATOR, HASH_LENGTH, MAX_PDA_SEEDS}, 
+    utils::verify_merkle_proof
+};
+
+/// Enum for the type of an account set
+/// Inline => 0
+/// Merkle => 1
+#[repr(u8)]
+pub enum AccountSetType {
+    Inline = 0,
+    Merkle = 1
+}
+
+impl TryFrom<u8> for AccountSetType {
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AccountSetType::Inline),
+            1 => Ok(AccountSetType::Merkle),
+            _ => Err(WrapperError::InvalidAccountSet),
+        }
+    }
+
+    type Error = WrapperError;
+}
+
+/// Stores the set of keys the account at a committed position can be, the position
+/// is that of the account in the account indices, with `Inline` the keys are stored
+/// in the commit account and with `Merkle` only the root of the tree of the keys is
+/// stored and the proof(proof length hashes) is passed when invoking
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct AccountSet {
+    pub position: u8,
+    pub set_type: u8,
+    pub key_count: u8,
+    pub proof_length: u8,
+    pub root: [u8; HASH_LENGTH],
+}
+
+impl AccountSet{
+    pub const LEN:usize = core::mem::size_of::<AccountSet>();
+}
+
+impl AccountSet{
+
+    /// Checks if the set is valid for it's type, an `Inline` set must have at least one
+    /// key and no root or proof, a `Merkle` set must have no keys
+    pub fn is_valid(&self)->Result<(), WrapperError>{
+        let is_valid = match AccountSetType::try_from(self.set_type)? {
+            AccountSetType::Inline => self.key_count.ne(&0) && self.proof_length.eq(&0) &&
+                self.root.iter().all(|byte| byte.eq(&0)),
+            AccountSetType::Merkle => self.key_count.eq(&0),
+        };
+
+        if !is_valid{
+            return Err(WrapperError::InvalidAccountSet);
+        }
+
+        Ok(())
+    }
+
+    /// Gets the number of bytes of the proof passed when invoking
+    #[inline(always)]
+    pub fn get_proof_len(&self)->usize{
+        usize::from(self.proof_length) * HASH_LENGTH
+    }
+
+    /// Checks if the key is part of the set, the keys are those of an `Inline` set and
+    /// the proof is that of a `Merkle` set
+    pub fn contains(&self, key:&Pubkey, keys:&[Pubkey], proof:&[u8])->Result<bool, WrapperError>{
+        let contains = match AccountSetType::try_from(self.set_type)? {
+            AccountSetType::Inline => keys.iter().any(|set_key| set_key.eq(key)),
+            AccountSetType::Merkle => verify_merkle_proof(key, proof, &self.root),
+        };
+
+        Ok(contains)
+    }
+}
+
+/// Stores the program the account at a committed position must be owned by, the checks
+/// set whether the data length and the discriminator(first eight bytes of the data) of
+/// the account are also checked:-
+/// bit 0 => data length
+/// bit 1 => discriminator
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct AccountOwnerConstraint {
+    pub position: u8,
+    pub checks: u8,
+    pub data_length: [u8; 4],
+    pub discriminator: [u8; 8],
+    pub owner: Pubkey,
+}
+
+impl AccountOwnerConstraint{
+    pub const LEN:usize = core::mem::size_of::<AccountOwnerConstraint>();
+}
+
+impl AccountOwnerConstraint{
+
+    /// Checks if only the known checks are set and the fields of the checks that are
+    /// not set are zero
+    pub fn is_valid(&self)->Result<(), WrapperError>{
+        let valid_checks = ACCOUNT_CHECK_DATA_LENGTH | ACCOUNT_CHECK_DISCRIMINATOR;
+
+        if (self.checks & !valid_checks).ne(&0) ||
+            ((self.checks & ACCOUNT_CHECK_DATA_LENGTH).eq(&0) && self.data_length.ne(&[0; 4])) ||
+            ((self.checks & ACCOUNT_CHECK_DISCRIMINATOR).eq(&0) && self.discriminator.ne(&[0; 8])){
+            return Err(WrapperError::InvalidAccountOwnerConstraint);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the account is owned by the program along with the data length and
+    /// discriminator when they are set
+    pub fn check(&self, account:&AccountInfo)->Result<(), ProgramError>{
+        if !account.is_owned_by(&self.owner){
+            return Err(WrapperError::AccountOwnerConstraintNotMet.into());
+        }
+
+        if (self.checks & ACCOUNT_CHECK_DATA_LENGTH).ne(&0) &&
+            account.data_len().ne(&(u32::from_le_bytes(self.data_length) as usize)){
+            return Err(WrapperError::AccountOwnerConstraintNotMet.into());
+        }
+
+        if (self.checks & ACCOUNT_CHECK_DISCRIMINATOR).ne(&0){
+            let data = account.try_borrow_data()?;
+
+            if data.get(..self.discriminator.len()).is_none_or(|prefix| prefix.ne(&self.discriminator)){
+                return Err(WrapperError::AccountOwnerConstraintNotMet.into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Enum for the type of a seed of a PDA constraint
+/// Constant => 0
+/// AccountKey => 1
+#[repr(u8)]
+pub enum PdaSeedType {
+    Constant = 0,
+    AccountKey = 1
+}
+
+impl TryFrom<u8> for PdaSeedType {
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(PdaSeedType::Constant),
+            1 => Ok(PdaSeedType::AccountKey),
+            _ => Err(WrapperError::InvalidPdaConstraint),
+        }
+    }
+
+    type Error = WrapperError;
+}
+
+/// Stores a seed of a PDA constraint, with `Constant` the seed is the first length
+/// bytes of the value and with `AccountKey` it is the key of the account at the
+/// committed position
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct PdaSeed {
+    pub seed_type: u8,
+    pub length: u8,
+    pub position: u8,
+    pub value: [u8; 32],
+}
+
+impl PdaSeed{
+    pub const LEN:usize = core::mem::size_of::<PdaSeed>();
+}
+
+impl PdaSeed{
+
+    /// Checks if the seed is valid for it's type, the unused bytes must be zero and
+    /// the key can not be that of the account being derived
+    pub fn is_valid(&self, account_count:u8, derived_position:u8)->Result<(), WrapperError>{
+        let length = usize::from(self.length);
+
+        let is_valid = match PdaSeedType::try_from(self.seed_type)? {
+            PdaSeedType::Constant => length.le(&self.value.len()) && self.position.eq(&0) &&
+                self.value[length..].iter().all(|byte| byte.eq(&0)),
+            PdaSeedType::AccountKey => length.eq(&0) && self.position.lt(&account_count) &&
+                self.position.ne(&derived_position) && self.value.iter().all(|byte| byte.eq(&0)),
+        };
+
+        if !is_valid{
+            return Err(WrapperError::InvalidPdaConstraint);
+        }
+
+        Ok(())
+    }
+}
+
+/// Stores how the account at a committed position is derived, it must be the PDA of
+/// the program with the seeds(seed count of them, stored one after another for all
+/// the constraints) followed by the bump at the bump offset of the instruction data
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct AccountPdaConstraint {
+    pub position: u8,
+    pub seed_count: u8,
+    pub bump_offset: [u8; 2],
+    pub program: Pubkey,
+}
+
+impl AccountPdaConstraint{
+    pub const LEN:usize = core::mem::size_of::<AccountPdaConstraint>();
+}
+
+impl AccountPdaConstraint{
+
+    /// Checks if the seeds are valid and the bump is within instruction data of the given
+    /// maximum length when it is bounded, the bump is counted as one of the seeds
+    pub fn is_valid(&self, seeds:&[PdaSeed], account_count:u8, 
+        instruction_data_length:Option<usize>)->Result<(), WrapperError>{
+        if usize::from(self.seed_count).ge(&MAX_PDA_SEEDS){
+            return Err(WrapperError::InvalidPdaConstraint);
+        }
+
+        if let Some(instruction_data_length) = instruction_data_length {
+            if usize::from(u16::from_le_bytes(self.bump_offset)).ge(&instruction_data_length){
+                return Err(WrapperError::InvalidPdaConstraint);
+            }
+        }
+
+        for seed in seeds{
+            seed.is_valid(account_count, self.position)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Stores an assertion on the data of the account at a committed position, the length
+/// bytes at the offset are compared against the first length bytes of the value, ordered
+/// comparisons read them as a little endian unsigned integer of at most eight bytes
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct AccountDataAssertion {
+    pub position: u8,
+    pub comparison: u8,
+    pub length: u8,
+    pub offset: [u8; 4],
+    pub value: [u8; 32],
+}
+
+impl AccountDataAssertion{
+    pub const LEN:usize = core::mem::size_of::<AccountDataAssertion>();
+}
+
+impl AccountDataAssertion{
+
+    /// Checks if the assertion is valid, the position must be one of the committed
+    /// accounts, the length must fit the value(and a u64 for ordered comparisons) and
+    /// the unused bytes of the value must be zero
+    pub fn is_valid(&self, account_count:u8)->Result<(), WrapperError>{
+        let comparison = Comparison::try_from(self.comparison).
+            map_err(|_| WrapperError::InvalidAccountDataAssertion)?;
+
+        let length = usize::from(self.length);
+
+        let max_length = match comparison {
+            Comparison::Equal | Comparison::NotEqual => self.value.len(),
+            _ => core::mem::size_of::<u64>(),
+        };
+
+        if self.position.ge(&account_count) || length.eq(&0) || length.gt(&max_length) ||
+            self.value[length..].iter().any(|byte| byte.ne(&0)){
+            return Err(WrapperError::InvalidAccountDataAssertion);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the data of the account holds the assertion, it is not met when the
+    /// data is too short
+    pub fn check(&self, account:&AccountInfo)->Result<(), ProgramError>{
+        let comparison = Comparison::try_from(self.comparison)?;
+
+        let offset = u32::from_le_bytes(self.offset) as usize;
+
+        let length = usize::from(self.length);
+
+        let data = account.try_borrow_data()?;
+
+        let data_bytes = offset.checked_add(length).
+            and_then(|end| data.get(offset..end)).
+            ok_or(WrapperError::AccountDataAssertionNotMet)?;
+
+        let expected_bytes = &self.value[..length];
+
+        let holds = match comparison {
+            Comparison::Equal | Comparison::NotEqual => comparison.holds(&data_bytes, &expected_bytes),
+            _ => comparison.holds(&Self::decode(data_bytes), &Self::decode(expected_bytes)),
+        };
+
+        if !holds{
+            return Err(WrapperError::AccountDataAssertionNotMet.into());
+        }
+
+        Ok(())
+    }
+
+    /// Decodes at most eight little endian bytes as a u64
+    #[inline(always)]
+    fn decode(bytes:&[u8])->u64{
+        let mut value_bytes:[u8;8] = [0;8];
+
+        value_bytes[..bytes.len()].copy_from_slice(bytes);
+
+        u64::from_le_bytes(value_bytes)
+    }
+}
+
+/// Enum for the type of an account invariant
+/// FieldUnchanged => 0
+/// DataLengthUnchanged => 1
+/// OwnerUnchanged => 2
+#[repr(u8)]
+pub enum AccountInvariantType {
+    FieldUnchanged = 0,
+    DataLengthUnchanged = 1,
+    OwnerUnchanged = 2
+}
+
+impl TryFrom<u8> for AccountInvariantType {
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AccountInvariantType::FieldUnchanged),
+            1 => Ok(AccountInvariantType::DataLengthUnchanged),
+            2 => Ok(AccountInvariantType::OwnerUnchanged),
+            _ => Err(WrapperError::InvalidAccountInvariant),
+        }
+    }
+
+    type Error = WrapperError;
+}
+
+/// Stores the state an account invariant reads, the field bytes, the data length as a
+/// little endian u64 or the owner, it is zero padded
+pub type AccountInvariantState = [u8; 32];
+
+/// Stores an invariant on the account at a committed position, the state it reads is
+/// taken before the program is invoked and must be the same after, with `FieldUnchanged`
+/// the state is the length bytes at the offset of the data
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct AccountInvariant {
+    pub position: u8,
+    pub invariant_type: u8,
+    pub length: u8,
+    pub offset: [u8; 4],
+}
+
+impl AccountInvariant{
+    pub const LEN:usize = core::mem::size_of::<AccountInvariant>();
+}
+
+impl AccountInvariant{
+
+    /// Checks if the invariant is valid for it's type, the position must be one of the
+    /// committed accounts, the field must fit the state and the unused fields must be zero
+    pub fn is_valid(&self, account_count:u8)->Result<(), WrapperError>{
+        let length = usize::from(self.length);
+
+        let is_valid = self.position.lt(&account_count) && 
+            match AccountInvariantType::try_from(self.invariant_type)? {
+                AccountInvariantType::FieldUnchanged => length.ne(&0) && 
+                    length.le(&core::mem::size_of::<AccountInvariantState>()),
+                AccountInvariantType::DataLengthUnchanged | AccountInvariantType::OwnerUnchanged => 
+                    length.eq(&0) && self.offset.eq(&[0; 4]),
+            };
+
+        if !is_valid{
+            return Err(WrapperError::InvalidAccountInvariant);
+        }
+
+        Ok(())
+    }
+
+    /// Gets the state of the account the invariant reads, the invariant is not met when
+    /// the data is too short for the field
+    pub fn get_state(&self, account:&AccountInfo)->Result<AccountInvariantState, ProgramError>{
+        let mut state:AccountInvariantState = [0; 32];
+
+        match AccountInvariantType::try_from(self.invariant_type)? {
+            AccountInvariantType::FieldUnchanged => {
+                let offset = u32::from_le_bytes(self.offset) as usize;
+
+                let length = usize::from(self.length);
+
+                let data = account.try_borrow_data()?;
+
+                let field_bytes = offset.checked_add(length).
+                    and_then(|end| data.get(offset..end)).
+                    ok_or(WrapperError::AccountInvariantNotMet)?;
+
+                state[..length].copy_from_slice(field_bytes);
+            },
+            AccountInvariantType::DataLengthUnchanged => {
+                state[..core::mem::size_of::<u64>()].copy_from_slice(&(account.data_len() as u64).to_le_bytes());
+            },
+            AccountInvariantType::OwnerUnchanged => {
+                state.copy_from_slice(account.owner());
+            },
+        }
+
+        Ok(state)
+    }
+
+    /// Checks if the state of the account is the same as the state taken before the
+    /// program was invoked
+    pub fn check(&self, account:&AccountInfo, state:&AccountInvariantState)->Result<(), ProgramError>{
+        if self.get_state(account)?.ne(state){
+            return Err(WrapperError::AccountInvariantNotMet.into());
+        }
+
+        Ok(())
+    }
+}
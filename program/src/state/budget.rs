@@ -0,0 +1,154 @@
+use bytemuck::{
+    Pod,
+    Zeroable
+};
+
+use pinocchio::{
+    program_error::ProgramError, sysvars::{
+        clock::Clock,
+        Sysvar
+    }
+};
+
+use crate::{
+    WrapperError
+};
+
+/// Enum for the value budget tag
+/// NoBudget => 0
+/// Lifetime => 1
+/// Window => 2
+/// LifetimeAndWindow => 3
+#[repr(u8)]
+pub enum ValueBudgetTag {
+    NoBudget = 0,
+    Lifetime = 1,
+    Window = 2,
+    LifetimeAndWindow = 3
+}
+
+impl TryFrom<u8> for ValueBudgetTag {
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ValueBudgetTag::NoBudget),
+            1 => Ok(ValueBudgetTag::Lifetime),
+            2 => Ok(ValueBudgetTag::Window),
+            3 => Ok(ValueBudgetTag::LifetimeAndWindow),
+            _ => Err(WrapperError::InvalidValueBudget),
+        }
+    }
+
+    type Error = WrapperError;
+}
+
+/// Stores the budget of the value moved by the commit, the value of each call
+/// is the little endian u64 at the amount offset of the instruction data, the
+/// values are added to a running total which can not go over the budget.
+///
+/// The windows are anchored to the unix epoch so they line up regardless of
+/// when the commit is used, the window total is reset at the start of each window.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct ValueBudget {
+    pub tag: u8,
+    pub amount_offset: [u8; 2],
+    pub lifetime_budget: [u8; 8],
+    pub window_budget: [u8; 8],
+    pub window_length: [u8; 4],
+    pub lifetime_spent: [u8; 8],
+    pub window_spent: [u8; 8],
+    pub window_start: [u8; 8],
+}
+
+impl ValueBudget{
+    pub const LEN:usize = core::mem::size_of::<ValueBudget>();
+}
+
+impl ValueBudget{
+
+    /// Checks if the tag is valid and the amount can be read from instruction data
+    /// of the given maximum length, if so it returns the tag
+    pub fn is_valid(&self, instruction_data_length:Option<usize>)->Result<ValueBudgetTag, WrapperError>{
+        let tag = ValueBudgetTag::try_from(self.tag)?;
+
+        if matches!(tag, ValueBudgetTag::NoBudget){
+            return Ok(tag);
+        }
+
+        // The amount must be within the instruction data when it's length is bounded
+        if let Some(instruction_data_length) = instruction_data_length {
+            let amount_end = usize::from(u16::from_le_bytes(self.amount_offset)) +
+                core::mem::size_of::<u64>();
+
+            if amount_end.gt(&instruction_data_length){
+                return Err(WrapperError::InvalidValueBudget);
+            }
+        }
+
+        // The window is used as a divisor
+        if matches!(tag, ValueBudgetTag::Window | ValueBudgetTag::LifetimeAndWindow) &&
+            u32::from_le_bytes(self.window_length).eq(&0){
+            return Err(WrapperError::InvalidValueBudget);
+        }
+
+        Ok(tag)
+    }
+
+    /// Adds the amount to the spent total, returns an error if it would go over the budget
+    #[inline(always)]
+    fn spend(spent: &mut [u8; 8], budget: &[u8; 8], amount: u64)->Result<(), ProgramError>{
+        let total = u64::from_le_bytes(*spent).checked_add(amount).
+            ok_or(WrapperError::ValueBudgetExceeded)?;
+
+        if total.gt(&u64::from_le_bytes(*budget)){
+            return Err(WrapperError::ValueBudgetExceeded.into());
+        }
+
+        *spent = total.to_le_bytes();
+
+        Ok(())
+    }
+
+    /// Reads the amount from the instruction data and adds it to the running totals,
+    /// returns an error if any of the budgets would be exceeded
+    pub fn update(&mut self, instruction_data:&[u8])->Result<(), ProgramError>{
+        let tag = ValueBudgetTag::try_from(self.tag)?;
+
+        if matches!(tag, ValueBudgetTag::NoBudget){
+            return Ok(());
+        }
+
+        let amount_offset = usize::from(u16::from_le_bytes(self.amount_offset));
+
+        let amount_bytes = instruction_data.
+            get(amount_offset..(amount_offset + core::mem::size_of::<u64>())).
+            ok_or(ProgramError::InvalidInstructionData)?;
+
+        let amount = u64::from_le_bytes(amount_bytes.try_into().unwrap());
+
+        if matches!(tag, ValueBudgetTag::Lifetime | ValueBudgetTag::LifetimeAndWindow){
+            Self::spend(&mut self.lifetime_spent, &self.lifetime_budget, amount)?;
+        }
+
+        if matches!(tag, ValueBudgetTag::Window | ValueBudgetTag::LifetimeAndWindow){
+            let current_timestamp = Clock::get()?.unix_timestamp;
+
+            let window_length = i64::from(u32::from_le_bytes(self.window_length));
+
+            let window_start = current_timestamp.checked_sub(
+                current_timestamp.checked_rem_euclid(window_length).
+                    ok_or_else(||ProgramError::ArithmeticOverflow)?).
+                ok_or_else(||ProgramError::ArithmeticOverflow)?;
+
+            // Reset the total at the start of a new window
+            if window_start.gt(&i64::from_le_bytes(self.window_start)){
+                self.window_start = window_start.to_le_bytes();
+                self.window_spent = [0; 8];
+            }
+
+            Self::spend(&mut self.window_spent, &self.window_budget, amount)?;
+        }
+
+        Ok(())
+    }
+}
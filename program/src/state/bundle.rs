@@ -0,0 +1,84 @@
+use bytemuck::{
+    Pod,
+    Zeroable
+};
+
+use pinocchio::{
+    program_error::ProgramError
+};
+
+use crate::{
+    constants::HASH_LENGTH,
+    form::FormData,
+    utils::hashv
+};
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+/// Stores the lengths of an instruction of a bundle commit, it is passed in the Entry
+/// instruction data before the form(the commit form followed by it's sections) and the
+/// data of the instruction, the instruction uses the next account count accounts
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct BundleInstruction {
+    pub account_count: u8,
+    pub form_length: [u8; 2],
+    pub data_length: [u8; 2],
+}
+
+impl BundleInstruction{
+    pub const LEN:usize = core::mem::size_of::<BundleInstruction>();
+}
+
+/// Stores an instruction of a bundle commit along with it's form, data and the proofs
+/// of it's `Merkle` account sets
+#[derive(Debug, Clone, Copy)]
+pub struct BundleInstructionData<'a> {
+    pub instruction: &'a BundleInstruction,
+    pub form: FormData<'a>,
+    pub program_data: &'a [u8],
+    pub account_set_proofs: &'a [u8],
+}
+
+impl<'a> BundleInstructionData<'a>{
+
+    /// Splits the instruction off the start of the data, returns it with the rest of the data
+    /// Bundle instruction -- Form -- Program data -- [Account set proofs] -- [Rest of the data]
+    pub fn try_split_from(data:&'a [u8])->Result<(Self, &'a [u8]), ProgramError>{
+        let (instruction, data) = Self::split_start(data, BundleInstruction::LEN)?;
+
+        let instruction = bytemuck::try_from_bytes::<BundleInstruction>(instruction).
+            map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let (form_data, data) = Self::split_start(data, usize::from(u16::from_le_bytes(instruction.form_length)))?;
+
+        let form = FormData::try_from_bytes(form_data)?;
+
+        let (program_data, data) = Self::split_start(data, usize::from(u16::from_le_bytes(instruction.data_length)))?;
+
+        let (account_set_proofs, data) = Self::split_start(data, form.sections.get_account_set_proofs_len())?;
+
+        Ok((BundleInstructionData{ instruction, form, program_data, account_set_proofs }, data))
+    }
+
+    /// Splits `length` bytes off the start of the data
+    #[inline(always)]
+    fn split_start(data:&'a [u8], length:usize)->Result<(&'a [u8], &'a [u8]), ProgramError>{
+        if data.len().lt(&length){
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(data.split_at(length))
+    }
+}
+
+/// Gets the hash of a bundle, each instruction is the hash of the seeds of it's form without
+/// the bump and the bundle is the hash of the instructions in the order they are executed
+pub fn get_bundle_hash(instruction_hashes:&[[u8; HASH_LENGTH]], bundle_hash:&mut [u8; HASH_LENGTH]){
+    let instruction_hashes:Vec<&[u8]> = instruction_hashes.iter().
+        map(|instruction_hash| instruction_hash.as_ref()).collect();
+
+    hashv(&instruction_hashes, bundle_hash);
+}
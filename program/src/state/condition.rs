@@ -4,18 +4,27 @@ use bytemuck::{
 };
 
 use pinocchio::{
-    msg, program_error::ProgramError, sysvars::{
-        clock::Clock, 
-        Sysvar
+    account_info::AccountInfo, msg, program_error::ProgramError, sysvars::{
+        clock::Clock
     }
 };
 
 use crate::{
-    WrapperError
+    WrapperError, constants::{CONFIG_MAX_SUB_CONDITIONS, SLIDING_WINDOW_MAX_USES}
 };
 
 pub const COMMIT_CONDITION_DATA_SIZE:usize = 20;
 
+const SECONDS_PER_MINUTE:i64 = 60;
+const MINUTES_PER_DAY:u16 = 1440;
+const SECONDS_PER_DAY:i64 = 86400;
+const DAYS_PER_WEEK:i64 = 7;
+/// The unix epoch(day zero) was a Thursday, this shifts it so that Monday is day zero
+const UNIX_EPOCH_WEEKDAY:i64 = 3;
+const ALL_WEEKDAYS_MASK:u8 = 0b0111_1111;
+const HALF_COUNT_BITS:u32 = 16;
+const HALF_COUNT_MASK:u32 = 0xFFFF;
+
 #[repr(u8)]
 pub enum CommitConditionTag {
     Default = 0,
@@ -25,7 +34,20 @@ pub enum CommitConditionTag {
     CountBeforeTimestamp = 4,
     CountAfterTimestamp = 5,
     OneOffCountBetweenTimestamp = 6,
-    RepeatCountBetweenTimestamp = 7
+    RepeatCountBetweenTimestamp = 7,
+    BeforeSlot = 8,
+    AfterSlot = 9,
+    CountBeforeSlot = 10,
+    CountBetweenSlots = 11,
+    CountPerEpochs = 12,
+    CountCooldownTimestamp = 13,
+    CountCooldownSlot = 14,
+    All = 15,
+    Any = 16,
+    SlidingWindowCount = 17,
+    DailyWindow = 18,
+    CappedRepeatCountBetweenTimestamp = 19,
+    AccountValue = 20
 }
 
 impl TryFrom<u8> for CommitConditionTag {
@@ -39,6 +61,19 @@ impl TryFrom<u8> for CommitConditionTag {
             5 => Ok(CommitConditionTag::CountAfterTimestamp),
             6 => Ok(CommitConditionTag::OneOffCountBetweenTimestamp),
             7 => Ok(CommitConditionTag::RepeatCountBetweenTimestamp),
+            8 => Ok(CommitConditionTag::BeforeSlot),
+            9 => Ok(CommitConditionTag::AfterSlot),
+            10 => Ok(CommitConditionTag::CountBeforeSlot),
+            11 => Ok(CommitConditionTag::CountBetweenSlots),
+            12 => Ok(CommitConditionTag::CountPerEpochs),
+            13 => Ok(CommitConditionTag::CountCooldownTimestamp),
+            14 => Ok(CommitConditionTag::CountCooldownSlot),
+            15 => Ok(CommitConditionTag::All),
+            16 => Ok(CommitConditionTag::Any),
+            17 => Ok(CommitConditionTag::SlidingWindowCount),
+            18 => Ok(CommitConditionTag::DailyWindow),
+            19 => Ok(CommitConditionTag::CappedRepeatCountBetweenTimestamp),
+            20 => Ok(CommitConditionTag::AccountValue),
             _ => Err(WrapperError::InvalidCommitConditionTag),
         }
     }
@@ -57,13 +92,75 @@ impl CommitConditionTag {
     #[inline(always)]
     pub fn is_valid(value:u8) -> bool {
         match value {
-            0 | 1 | 2 | 3 | 4 | 5 | 6 | 7 => true,
+            0 | 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10 | 11 | 12 | 13 | 14 | 15 | 16 | 17 | 18 | 19 | 20 => true,
             _ => false,
         }
     }
+
+    /// Checks if the tag combines the sub-conditions rather than being
+    /// evaluated on its own
+    #[inline(always)]
+    pub fn is_composite(&self) -> bool {
+        matches!(self, CommitConditionTag::All | CommitConditionTag::Any)
+    }
     
 }
 
+/// The comparisons that can be made between a value and the value it is checked against
+/// Equal => 0
+/// Less => 1
+/// Greater => 2
+/// LessOrEqual => 3
+/// GreaterOrEqual => 4
+/// NotEqual => 5
+#[repr(u8)]
+pub enum Comparison {
+    Equal = 0,
+    Less = 1,
+    Greater = 2,
+    LessOrEqual = 3,
+    GreaterOrEqual = 4,
+    NotEqual = 5
+}
+
+impl TryFrom<u8> for Comparison {
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Comparison::Equal),
+            1 => Ok(Comparison::Less),
+            2 => Ok(Comparison::Greater),
+            3 => Ok(Comparison::LessOrEqual),
+            4 => Ok(Comparison::GreaterOrEqual),
+            5 => Ok(Comparison::NotEqual),
+            _ => Err(WrapperError::InvalidComparison),
+        }
+    }
+
+    type Error = WrapperError;
+}
+
+impl Comparison {
+    /// Checks if the value compares to the expected value as required
+    #[inline(always)]
+    pub fn holds<T: PartialOrd>(&self, value:&T, expected:&T) -> bool {
+        match self {
+            Comparison::Equal => value.eq(expected),
+            Comparison::Less => value.lt(expected),
+            Comparison::Greater => value.gt(expected),
+            Comparison::LessOrEqual => value.le(expected),
+            Comparison::GreaterOrEqual => value.ge(expected),
+            Comparison::NotEqual => value.ne(expected),
+        }
+    }
+}
+
+/// Stores what a condition is checked against, the clock and the account
+/// whose data the `AccountValue` condition reads if one was passed
+pub struct ConditionContext<'a> {
+    pub clock: Clock,
+    pub condition_account: Option<&'a AccountInfo>,
+}
+
 /// Stores information about the time form of the commitment made
 #[repr(C, packed)]
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
@@ -76,12 +173,346 @@ impl CommitCondition{
     pub const LEN:usize = core::mem::size_of::<CommitCondition>();
 }
 
+/// Stores the timestamps of the most recent uses of a commit, it is used
+/// as a ring buffer by the `SlidingWindowCount` condition
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct UsageWindow {
+    pub timestamps: [[u8; 8]; SLIDING_WINDOW_MAX_USES],
+}
+
+impl UsageWindow{
+    pub const LEN:usize = core::mem::size_of::<UsageWindow>();
+}
+
 impl CommitCondition{
 
-    /// Checks if the tag is valid, if so it returns it
+    /// Checks if the tag is valid along with any data the tag can not
+    /// work without, if so it returns it
     #[inline(always)]
     pub fn is_valid(&self)->Result<CommitConditionTag, WrapperError>{
-        CommitConditionTag::try_from(self.tag)
+        let tag = CommitConditionTag::try_from(self.tag)?;
+
+        match tag {
+            // The epoch period is used as a divisor
+            CommitConditionTag::CountPerEpochs=>{
+                let epochs_per_period = u32::from_le_bytes(
+                    self.data[12..16].try_into().unwrap());
+
+                if epochs_per_period.eq(&0){
+                    return Err(WrapperError::InvalidCommitCondition);
+                }
+            },
+            // The number of uses is the size of the ring buffer
+            CommitConditionTag::SlidingWindowCount=>{
+                let max_uses = u32::from_le_bytes(
+                    self.data[16..20].try_into().unwrap());
+
+                if max_uses.eq(&0) || (max_uses as usize).gt(&SLIDING_WINDOW_MAX_USES){
+                    return Err(WrapperError::InvalidCommitCondition);
+                }
+            },
+            // The comparison must be known
+            CommitConditionTag::AccountValue=>{
+                let (comparison, _, _) = self.get_account_value_fields();
+
+                Comparison::try_from(comparison)?;
+            },
+            // The window must be within a single day and on at least one weekday
+            CommitConditionTag::DailyWindow=>{
+                let (start_minute, end_minute, weekday_mask, _) = 
+                    self.get_daily_window_fields();
+
+                if start_minute.ge(&end_minute) || end_minute.gt(&MINUTES_PER_DAY) ||
+                    weekday_mask.eq(&0) || (weekday_mask & !ALL_WEEKDAYS_MASK).ne(&0){
+                    return Err(WrapperError::InvalidCommitCondition);
+                }
+            },
+            _=>{}
+        }
+
+        Ok(tag)
+    }
+
+    /// Checks if the condition is valid when evaluated along with the given
+    /// sub-conditions, a composite condition must reference at least one and at
+    /// most all of the sub-conditions, and each of those must be a valid
+    /// non-composite condition, if so it returns the tag
+    pub fn is_valid_with(&self, sub_conditions:&[CommitCondition])->Result<CommitConditionTag, WrapperError>{
+        let tag = self.is_valid()?;
+
+        if !tag.is_composite(){
+            return Ok(tag);
+        }
+
+        let (sub_condition_count, negate_mask) = self.get_composite_fields();
+
+        if sub_condition_count.eq(&0) || sub_condition_count.gt(&sub_conditions.len()){
+            return Err(WrapperError::InvalidCommitCondition);
+        }
+
+        // Only the sub-conditions in use can be negated
+        if negate_mask.checked_shr(sub_condition_count as u32).unwrap_or(0).ne(&0){
+            return Err(WrapperError::InvalidCommitCondition);
+        }
+
+        let mut window_condition_count:usize = 0;
+
+        for sub_condition in sub_conditions.iter().take(sub_condition_count){
+            let sub_tag = sub_condition.is_valid()?;
+
+            if sub_tag.is_composite() || matches!(sub_tag, CommitConditionTag::Default){
+                return Err(WrapperError::InvalidCommitCondition);
+            }
+
+            // The usage window can only be shared by one condition
+            if matches!(sub_tag, CommitConditionTag::SlidingWindowCount){
+                window_condition_count += 1;
+            }
+        }
+
+        if window_condition_count.gt(&1){
+            return Err(WrapperError::InvalidCommitCondition);
+        }
+
+        Ok(tag)
+    }
+
+    /// Checks if the condition or any of the given sub-conditions it combines reads
+    /// the data of the condition account
+    pub fn uses_condition_account_with(&self, sub_conditions:&[CommitCondition]) -> bool {
+        self.uses_tag_with(sub_conditions, CommitConditionTag::AccountValue)
+    }
+
+    /// Checks if the condition or any of the given sub-conditions it combines records
+    /// the recent uses in the usage window
+    pub fn uses_usage_window_with(&self, sub_conditions:&[CommitCondition]) -> bool {
+        self.uses_tag_with(sub_conditions, CommitConditionTag::SlidingWindowCount)
+    }
+
+    /// Checks if the condition or any of the given sub-conditions it combines is of the
+    /// given non-composite tag
+    fn uses_tag_with(&self, sub_conditions:&[CommitCondition], expected_tag:CommitConditionTag) -> bool {
+        let expected_tag = expected_tag as u8;
+
+        let is_expected_tag = |condition:&CommitCondition| 
+            condition.is_valid().is_ok_and(|tag| (tag as u8).eq(&expected_tag));
+
+        match self.is_valid() {
+            Ok(tag) if tag.is_composite() => sub_conditions.iter().
+                take(self.get_sub_condition_count()).any(is_expected_tag),
+            Ok(tag) => (tag as u8).eq(&expected_tag),
+            _ => false,
+        }
+    }
+
+    /// Gets the number of sub-conditions the condition combines, a condition that is
+    /// not composite combines none
+    pub fn get_sub_condition_count(&self) -> usize {
+        match self.is_valid() {
+            Ok(tag) if tag.is_composite() => self.get_composite_fields().0,
+            _ => 0,
+        }
+    }
+
+    /// Checks if the condition can never be met again, only the conditions whose state
+    /// can not be refilled or whose time has passed are considered, a condition that
+    /// was not set is never considered expired as it is expected to be set later
+    pub fn is_expired(&self, clock:&Clock)->Result<bool, ProgramError>{
+        let tag = CommitConditionTag::try_from(self.tag)?;
+
+        let (count, timestamp, offset, repeat_count) = self.get_data_fields()?;
+
+        let slot = timestamp as u64;
+
+        let expired = match tag {
+            CommitConditionTag::Count |
+            CommitConditionTag::CountAfterTimestamp |
+            CommitConditionTag::CountCooldownTimestamp |
+            CommitConditionTag::CountCooldownSlot => count.eq(&0),
+            CommitConditionTag::BeforeTimestamp => clock.unix_timestamp.ge(&timestamp),
+            CommitConditionTag::CountBeforeTimestamp => 
+                count.eq(&0) || clock.unix_timestamp.ge(&timestamp),
+            CommitConditionTag::OneOffCountBetweenTimestamp => 
+                count.eq(&0) || timestamp.checked_add(i64::from(offset)).
+                    is_some_and(|end| clock.unix_timestamp.gt(&end)),
+            CommitConditionTag::RepeatCountBetweenTimestamp |
+            CommitConditionTag::CountPerEpochs => count.eq(&0) && repeat_count.eq(&0),
+            CommitConditionTag::BeforeSlot => clock.slot.ge(&slot),
+            CommitConditionTag::CountBeforeSlot => count.eq(&0) || clock.slot.ge(&slot),
+            CommitConditionTag::CountBetweenSlots => 
+                count.eq(&0) || slot.checked_add(u64::from(offset)).
+                    is_some_and(|end| clock.slot.gt(&end)),
+            // The lifetime count is stored in place of the repeat count
+            CommitConditionTag::CappedRepeatCountBetweenTimestamp => repeat_count.eq(&0),
+            CommitConditionTag::Default |
+            CommitConditionTag::AfterTimestamp |
+            CommitConditionTag::AfterSlot |
+            CommitConditionTag::SlidingWindowCount |
+            CommitConditionTag::DailyWindow |
+            CommitConditionTag::AccountValue |
+            CommitConditionTag::All |
+            CommitConditionTag::Any => false,
+        };
+
+        Ok(expired)
+    }
+
+    /// Checks if the composite condition can never be met again, `All` can not be met once
+    /// any of it's sub-conditions that are not negated expires and `Any` once all of it's
+    /// sub-conditions expire, a negated sub-condition is never considered expired
+    pub fn is_composite_expired(&self, sub_conditions:&[CommitCondition], clock:&Clock)->Result<bool, ProgramError>{
+        let tag = CommitConditionTag::try_from(self.tag)?;
+
+        let (sub_condition_count, negate_mask) = self.get_composite_fields();
+
+        let sub_conditions = sub_conditions.get(..sub_condition_count).
+            ok_or(WrapperError::InvalidCommitCondition)?;
+
+        let mut expired_count:usize = 0;
+
+        for (index, sub_condition) in sub_conditions.iter().enumerate(){
+            if (negate_mask >> index) & 1 == 0 && sub_condition.is_expired(clock)? {
+                expired_count += 1;
+            }
+        }
+
+        let expired = match tag {
+            CommitConditionTag::All => expired_count.gt(&0),
+            CommitConditionTag::Any => 
+                sub_condition_count.gt(&0) && expired_count.eq(&sub_condition_count),
+            _ => false,
+        };
+
+        Ok(expired)
+    }
+
+    /// Get the fields of a daily window condition, these are stored in place of the
+    /// offset and repeat count:-
+    /// start minute(u16), end minute(u16), weekday mask(u8), count per day(u16)
+    /// 
+    /// The minutes are counted from the start of the UTC day and the end is excluded, 
+    /// bit zero of the weekday mask is Monday and bit six is Sunday, a count per day of 
+    /// zero means the uses are not counted
+    #[inline(always)]
+    fn get_daily_window_fields(&self) -> (u16, u16, u8, u32) {
+        let start_minute = u16::from_le_bytes(self.data[12..14].try_into().unwrap());
+
+        let end_minute = u16::from_le_bytes(self.data[14..16].try_into().unwrap());
+
+        let weekday_mask = self.data[16];
+
+        let count_per_day = u16::from_le_bytes(self.data[17..19].try_into().unwrap());
+
+        (start_minute, end_minute, weekday_mask, u32::from(count_per_day))
+    }
+
+    /// Get the fields of an account value condition:-
+    /// comparison(u8), offset(u32), expected value(u64)
+    /// 
+    /// The little endian u64 at the offset of the condition account's data is compared
+    /// to the expected value
+    #[inline(always)]
+    fn get_account_value_fields(&self) -> (u8, usize, u64) {
+        let comparison = self.data[0];
+
+        let offset = u32::from_le_bytes(self.data[1..5].try_into().unwrap());
+
+        let expected_value = u64::from_le_bytes(self.data[5..13].try_into().unwrap());
+
+        (comparison, offset as usize, expected_value)
+    }
+
+    /// Get the fields of a composite condition, the number of sub-conditions
+    /// it combines and the mask of the sub-conditions that are negated
+    #[inline(always)]
+    fn get_composite_fields(&self) -> (usize, u8) {
+        (usize::from(self.data[0]), self.data[1])
+    }
+
+    /// Evaluates the sub-conditions combined by this composite condition, `All` requires
+    /// every sub-condition to pass and `Any` at least one, a negated sub-condition passes
+    /// when it would fail on its own, at most one sub-condition can use the usage window.
+    /// 
+    /// The sub-conditions are evaluated on a copy which is only written back once the
+    /// composite condition passes, a negated sub-condition never has it's state changed and
+    /// for `Any` only the sub-conditions that passed have their state changed.
+    pub fn update_composite(&self, sub_conditions:&mut [CommitCondition], context:&ConditionContext, 
+        usage_window:&mut UsageWindow)->Result<(), ProgramError>{
+        let tag = CommitConditionTag::try_from(self.tag)?;
+
+        let (sub_condition_count, negate_mask) = self.get_composite_fields();
+
+        let sub_conditions = sub_conditions.get_mut(..sub_condition_count).
+            ok_or(WrapperError::InvalidCommitCondition)?;
+
+        let mut updated_conditions = [CommitCondition::zeroed(); CONFIG_MAX_SUB_CONDITIONS];
+
+        let updated_conditions = updated_conditions.get_mut(..sub_condition_count).
+            ok_or(WrapperError::InvalidCommitCondition)?;
+
+        updated_conditions.copy_from_slice(sub_conditions);
+
+        let mut updated_usage_window = *usage_window;
+
+        let mut passed_count:usize = 0;
+
+        let mut last_error = ProgramError::from(WrapperError::ConditionNotSet);
+
+        for (index, updated_condition) in updated_conditions.iter_mut().enumerate(){
+            let result = if (negate_mask >> index) & 1 == 1 {
+                let mut negated_condition = *updated_condition;
+
+                let mut negated_usage_window = updated_usage_window;
+
+                // Only the errors that mean the condition was not met are inverted, any other
+                // error such as a missing or closed condition account fails the call
+                match negated_condition.update(context, &mut negated_usage_window) {
+                    Ok(()) => Err(WrapperError::NegatedConditionMet.into()),
+                    Err(error) if Self::is_not_met_error(&error) => Ok(()),
+                    Err(error) => Err(error),
+                }
+            }
+            else{
+                // The usage window is only changed when the condition passes
+                updated_condition.update(context, &mut updated_usage_window)
+            };
+
+            match result {
+                Ok(()) => {
+                    passed_count += 1;
+                },
+                Err(error) => {
+                    if matches!(tag, CommitConditionTag::All){
+                        return Err(error);
+                    }
+
+                    // Discard any change to the state of a failed sub-condition
+                    *updated_condition = sub_conditions[index];
+
+                    last_error = error;
+                }
+            }
+        }
+
+        if passed_count.eq(&0){
+            return Err(last_error);
+        }
+
+        sub_conditions.copy_from_slice(updated_conditions);
+
+        *usage_window = updated_usage_window;
+
+        Ok(())
+    }
+
+    /// Checks if the error returned by CommitCondition::update means the condition was
+    /// not met rather than that it could not be evaluated
+    #[inline(always)]
+    fn is_not_met_error(error:&ProgramError)->bool{
+        [WrapperError::TooEarly, WrapperError::TooLate, WrapperError::CountExhausted, 
+            WrapperError::AccountConditionNotMet].into_iter().
+            any(|not_met_error| error.eq(&ProgramError::from(not_met_error)))
     }
 
     /// Updates the count, returns an error if the count is exhausted
@@ -97,8 +528,8 @@ impl CommitCondition{
 
     /// Get all the data fields of the commit
     #[inline]
-    fn get_data_fields(&mut self) -> Result<(u32, i64, u32, u32), ProgramError> {
-        let (count_bytes, rest) = self.data.split_at_mut(4);
+    fn get_data_fields(&self) -> Result<(u32, i64, u32, u32), ProgramError> {
+        let (count_bytes, rest) = self.data.split_at(4);
 
         msg!("-6.1");
 
@@ -106,7 +537,7 @@ impl CommitCondition{
 
         msg!("-6.2");
 
-        let (timestamp_bytes, rest) = rest.split_at_mut(8);
+        let (timestamp_bytes, rest) = rest.split_at(8);
 
         msg!("-6.3");
 
@@ -114,7 +545,7 @@ impl CommitCondition{
 
         msg!("-6.4");
 
-        let (offset_bytes, repeat_count_bytes) = rest.split_at_mut(4);
+        let (offset_bytes, repeat_count_bytes) = rest.split_at(4);
 
         msg!("-6.5");
 
@@ -151,14 +582,26 @@ impl CommitCondition{
         Ok(())
     }
 
-    pub fn update(&mut self)->Result<(), ProgramError>{
-        let current_timestamp = Clock::get()?.unix_timestamp;
+    /// Checks the condition against the context and updates it's state, returns an error
+    /// if the condition is not met, composite conditions are evaluated through
+    /// `update_composite`
+    pub fn update(&mut self, context:&ConditionContext, usage_window:&mut UsageWindow)->Result<(), ProgramError>{
+        let clock = &context.clock;
+
+        let current_timestamp = clock.unix_timestamp;
+
+        let current_slot = clock.slot;
+
+        let current_epoch = clock.epoch;
 
         let tag = CommitConditionTag::try_from(self.tag)?;
 
         let (mut count, mut timestamp, offset, repeat_count) = 
             self.get_data_fields()?;
 
+        // The slot based conditions store the slot in place of the timestamp
+        let slot = timestamp as u64;
+
         match tag {
             CommitConditionTag::Count=>{
                 Self::update_count(&mut count)
@@ -219,6 +662,206 @@ impl CommitCondition{
 
                 Self::update_count(&mut count)
             },
+            CommitConditionTag::BeforeSlot=>{
+                if current_slot.ge(&slot){
+                    return Err(WrapperError::TooLate.into());
+                }
+
+                Ok(())
+            },
+            CommitConditionTag::AfterSlot=>{
+                if current_slot.le(&slot){
+                    return Err(WrapperError::TooEarly.into());
+                }
+
+                Ok(())
+            },
+            CommitConditionTag::CountBeforeSlot=>{
+                if current_slot.ge(&slot){
+                    return Err(WrapperError::TooLate.into());
+                }
+
+                Self::update_count(&mut count)
+            },
+            CommitConditionTag::CountBetweenSlots=>{
+                let start = slot;
+                let end = start.checked_add(u64::from(offset)).
+                    ok_or_else(||ProgramError::ArithmeticOverflow)?;
+
+                if current_slot.lt(&start){
+                    return Err(WrapperError::TooEarly.into());
+                }
+                else if current_slot.gt(&end) {
+                    return Err(WrapperError::TooLate.into());   
+                }
+
+                Self::update_count(&mut count)
+            },
+            CommitConditionTag::CountPerEpochs=>{
+                // The periods are anchored to epoch zero so they line up with the
+                // epoch boundaries regardless of when the commit is used
+                let period_start = current_epoch.checked_sub(
+                    current_epoch.checked_rem(u64::from(offset)).
+                        ok_or_else(||ProgramError::ArithmeticOverflow)?).
+                    ok_or_else(||ProgramError::ArithmeticOverflow)?;
+
+                // The epoch the current period started at is stored in place of the timestamp
+                let stored_period_start = timestamp as u64;
+
+                if period_start.gt(&stored_period_start){
+                    timestamp = period_start as i64;
+                    count = repeat_count;
+                }
+
+                Self::update_count(&mut count)
+            },
+            CommitConditionTag::CountCooldownTimestamp=>{
+                // The timestamp of the last successful call is stored in place of the timestamp
+                let next_timestamp = timestamp.checked_add(i64::from(offset)).
+                    ok_or_else(||ProgramError::ArithmeticOverflow)?;
+
+                if current_timestamp.lt(&next_timestamp){
+                    return Err(WrapperError::TooEarly.into());
+                }
+
+                timestamp = current_timestamp;
+
+                Self::update_count(&mut count)
+            },
+            CommitConditionTag::CountCooldownSlot=>{
+                // The slot of the last successful call is stored in place of the timestamp
+                let next_slot = slot.checked_add(u64::from(offset)).
+                    ok_or_else(||ProgramError::ArithmeticOverflow)?;
+
+                if current_slot.lt(&next_slot){
+                    return Err(WrapperError::TooEarly.into());
+                }
+
+                timestamp = current_slot as i64;
+
+                Self::update_count(&mut count)
+            },
+            CommitConditionTag::SlidingWindowCount=>{
+                // The position of the oldest use in the ring buffer is stored in place of the timestamp,
+                // it would be overwritten by this use
+                let max_uses = usize::try_from(repeat_count).
+                    map_err(|_| WrapperError::InvalidCommitCondition)?;
+
+                let uses = usage_window.timestamps.get_mut(..max_uses).
+                    ok_or(WrapperError::InvalidCommitCondition)?;
+
+                let position = (timestamp as usize).checked_rem(max_uses).
+                    ok_or(WrapperError::InvalidCommitCondition)?;
+
+                let oldest_timestamp = i64::from_le_bytes(uses[position]);
+
+                // If the oldest of the last `max_uses` uses is still within the window then
+                // this use would exceed the limit
+                let window_start = current_timestamp.checked_sub(i64::from(offset)).
+                    ok_or_else(||ProgramError::ArithmeticOverflow)?;
+
+                if oldest_timestamp.gt(&window_start){
+                    return Err(WrapperError::TooEarly.into());
+                }
+
+                uses[position] = current_timestamp.to_le_bytes();
+
+                timestamp = ((position + 1) % max_uses) as i64;
+
+                Ok(())
+            },
+            CommitConditionTag::CappedRepeatCountBetweenTimestamp=>{
+                // The count for the window and the count it is refilled with share the bytes of
+                // the count(u16 each), and the count left over the lifetime of the commit is stored
+                // in place of the repeat count
+                let mut window_count = count & HALF_COUNT_MASK;
+                let window_repeat_count = count >> HALF_COUNT_BITS;
+                let mut lifetime_count = repeat_count;
+
+                // Once the lifetime count is used up no window can refill it
+                if lifetime_count.eq(&0){
+                    return Err(WrapperError::CountExhausted.into());
+                }
+
+                let end = timestamp.checked_add(i64::from(offset)).
+                    ok_or_else(||ProgramError::ArithmeticOverflow)?;
+
+                if current_timestamp.lt(&timestamp){
+                    return Err(WrapperError::TooEarly.into());
+                }
+                else if current_timestamp.gt(&end) {
+                    timestamp = current_timestamp;
+                    window_count = window_repeat_count;
+                }
+
+                Self::update_count(&mut window_count)?;
+
+                Self::update_count(&mut lifetime_count)?;
+
+                count = window_count | (window_repeat_count << HALF_COUNT_BITS);
+
+                self.data[16..20].copy_from_slice(&lifetime_count.to_le_bytes());
+
+                Ok(())
+            },
+            CommitConditionTag::DailyWindow=>{
+                let (start_minute, end_minute, weekday_mask, count_per_day) = 
+                    self.get_daily_window_fields();
+
+                let day = current_timestamp.div_euclid(SECONDS_PER_DAY);
+
+                let minute = current_timestamp.rem_euclid(SECONDS_PER_DAY) / SECONDS_PER_MINUTE;
+
+                let weekday = (day + UNIX_EPOCH_WEEKDAY).rem_euclid(DAYS_PER_WEEK);
+
+                if (weekday_mask >> weekday) & 1 == 0 {
+                    return Err(WrapperError::TooEarly.into());
+                }
+
+                if minute.lt(&i64::from(start_minute)){
+                    return Err(WrapperError::TooEarly.into());
+                }
+                else if minute.ge(&i64::from(end_minute)) {
+                    return Err(WrapperError::TooLate.into());
+                }
+
+                if count_per_day.eq(&0){
+                    return Ok(());
+                }
+
+                // The day the count was last refilled is stored in place of the timestamp
+                if day.gt(&timestamp){
+                    timestamp = day;
+                    count = count_per_day;
+                }
+
+                Self::update_count(&mut count)
+            },
+            CommitConditionTag::AccountValue=>{
+                let (comparison, offset, expected_value) = self.get_account_value_fields();
+
+                let comparison = Comparison::try_from(comparison)?;
+
+                let condition_account = context.condition_account.
+                    ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+                let condition_account_data = condition_account.try_borrow_data()?;
+
+                let value_bytes = offset.checked_add(core::mem::size_of::<u64>()).
+                    and_then(|end| condition_account_data.get(offset..end)).
+                    ok_or(ProgramError::InvalidAccountData)?;
+
+                let value = u64::from_le_bytes(value_bytes.try_into().unwrap());
+
+                if !comparison.holds(&value, &expected_value){
+                    return Err(WrapperError::AccountConditionNotMet.into());
+                }
+
+                Ok(())
+            },
+            CommitConditionTag::All | CommitConditionTag::Any=>{
+                Err(WrapperError::InvalidCommitCondition.into())
+            },
             CommitConditionTag::Default=>{
                 Err(WrapperError::ConditionNotSet.into())
             }
@@ -227,4 +870,235 @@ impl CommitCondition{
         self.set_data_fields(count, timestamp)
 
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONDAY:i64 = 4 * SECONDS_PER_DAY;
+
+    const MINUTE:i64 = SECONDS_PER_MINUTE;
+
+    /// Gets a condition with the given data fields, see CommitCondition::get_data_fields
+    fn condition(tag:CommitConditionTag, count:u32, timestamp:i64, offset:u32, repeat_count:u32)->CommitCondition{
+        let mut condition = CommitCondition::zeroed();
+
+        condition.tag = tag as u8;
+
+        condition.data[0..4].copy_from_slice(&count.to_le_bytes());
+
+        condition.data[4..12].copy_from_slice(&timestamp.to_le_bytes());
+
+        condition.data[12..16].copy_from_slice(&offset.to_le_bytes());
+
+        condition.data[16..20].copy_from_slice(&repeat_count.to_le_bytes());
+
+        condition
+    }
+
+    /// Gets a composite condition over the first sub-condition count sub-conditions
+    fn composite(tag:CommitConditionTag, sub_condition_count:u8, negate_mask:u8)->CommitCondition{
+        let mut condition = CommitCondition::zeroed();
+
+        condition.tag = tag as u8;
+
+        condition.data[0] = sub_condition_count;
+
+        condition.data[1] = negate_mask;
+
+        condition
+    }
+
+    /// Gets a context at the given unix timestamp without a condition account
+    fn context<'a>(unix_timestamp:i64)->ConditionContext<'a>{
+        ConditionContext{
+            clock: Clock{
+                slot: 0,
+                epoch_start_timestamp: 0,
+                epoch: 0,
+                leader_schedule_epoch: 0,
+                unix_timestamp
+            },
+            condition_account: None
+        }
+    }
+
+    /// Gets the count stored in the condition
+    fn count(condition:&CommitCondition)->u32{
+        u32::from_le_bytes(condition.data[0..4].try_into().unwrap())
+    }
+
+    /// Gets the result of a call that failed with the error
+    fn error(error:WrapperError)->Result<(), ProgramError>{
+        Err(error.into())
+    }
+
+    #[test]
+    fn all_keeps_the_state_when_a_sub_condition_fails() {
+        let all = composite(CommitConditionTag::All, 2, 0);
+
+        let mut sub_conditions = [condition(CommitConditionTag::Count, 2, 0, 0, 0), 
+            condition(CommitConditionTag::BeforeTimestamp, 0, 100, 0, 0)];
+
+        let mut usage_window = UsageWindow::zeroed();
+
+        assert_eq!(all.update_composite(&mut sub_conditions, &context(200), &mut usage_window), 
+            error(WrapperError::TooLate));
+
+        assert_eq!(count(&sub_conditions[0]), 2);
+
+        assert_eq!(all.update_composite(&mut sub_conditions, &context(50), &mut usage_window), Ok(()));
+
+        assert_eq!(count(&sub_conditions[0]), 1);
+    }
+
+    #[test]
+    fn any_only_updates_the_sub_conditions_that_passed() {
+        let any = composite(CommitConditionTag::Any, 2, 0);
+
+        let mut sub_conditions = [condition(CommitConditionTag::CountBeforeTimestamp, 1, 100, 0, 0), 
+            condition(CommitConditionTag::Count, 1, 0, 0, 0)];
+
+        let mut usage_window = UsageWindow::zeroed();
+
+        assert_eq!(any.update_composite(&mut sub_conditions, &context(200), &mut usage_window), Ok(()));
+
+        assert_eq!(count(&sub_conditions[0]), 1);
+
+        assert_eq!(count(&sub_conditions[1]), 0);
+
+        assert_eq!(any.update_composite(&mut sub_conditions, &context(200), &mut usage_window), 
+            error(WrapperError::CountExhausted));
+    }
+
+    #[test]
+    fn negated_sub_condition_passes_when_not_met() {
+        let all = composite(CommitConditionTag::All, 2, 0b10);
+
+        let mut sub_conditions = [condition(CommitConditionTag::Count, 2, 0, 0, 0), 
+            condition(CommitConditionTag::AfterTimestamp, 0, 100, 0, 0)];
+
+        let mut usage_window = UsageWindow::zeroed();
+
+        assert_eq!(all.update_composite(&mut sub_conditions, &context(50), &mut usage_window), Ok(()));
+
+        assert_eq!(all.update_composite(&mut sub_conditions, &context(150), &mut usage_window), 
+            error(WrapperError::NegatedConditionMet));
+
+        assert_eq!(count(&sub_conditions[0]), 1);
+    }
+
+    #[test]
+    fn negated_sub_condition_propagates_other_errors() {
+        let all = composite(CommitConditionTag::All, 1, 0b1);
+
+        // Reads the condition account which is not passed
+        let mut sub_conditions = [condition(CommitConditionTag::AccountValue, 0, 0, 0, 0)];
+
+        let mut usage_window = UsageWindow::zeroed();
+
+        assert_eq!(all.update_composite(&mut sub_conditions, &context(50), &mut usage_window), 
+            Err(ProgramError::NotEnoughAccountKeys));
+    }
+
+    #[test]
+    fn unused_sub_conditions_are_not_read() {
+        let all = composite(CommitConditionTag::All, 1, 0);
+
+        let sub_conditions = [condition(CommitConditionTag::Count, 2, 0, 0, 0), 
+            condition(CommitConditionTag::SlidingWindowCount, 2, 0, 0, 2)];
+
+        assert!(!all.uses_usage_window_with(&sub_conditions));
+
+        assert!(composite(CommitConditionTag::All, 2, 0).uses_usage_window_with(&sub_conditions));
+    }
+
+    #[test]
+    fn sliding_window_overwrites_the_oldest_use() {
+        // At most two uses within 100 seconds
+        let mut sliding_window = condition(CommitConditionTag::SlidingWindowCount, 0, 0, 100, 2);
+
+        let mut usage_window = UsageWindow::zeroed();
+
+        assert_eq!(sliding_window.update(&context(1000), &mut usage_window), Ok(()));
+
+        assert_eq!(sliding_window.update(&context(1010), &mut usage_window), Ok(()));
+
+        assert_eq!(sliding_window.update(&context(1020), &mut usage_window), 
+            error(WrapperError::TooEarly));
+
+        // The use at 1000 is outside the window so it is overwritten
+        assert_eq!(sliding_window.update(&context(1101), &mut usage_window), Ok(()));
+
+        assert_eq!(i64::from_le_bytes(usage_window.timestamps[0]), 1101);
+
+        assert_eq!(sliding_window.update(&context(1105), &mut usage_window), 
+            error(WrapperError::TooEarly));
+
+        assert_eq!(sliding_window.update(&context(1111), &mut usage_window), Ok(()));
+
+        assert_eq!(i64::from_le_bytes(usage_window.timestamps[1]), 1111);
+    }
+
+    #[test]
+    fn daily_window_checks_the_weekday_and_minutes() {
+        // Once a day on Mondays between 09:00 and 17:00
+        let mut daily_window = condition(CommitConditionTag::DailyWindow, 0, 0, 0, 0);
+
+        daily_window.data[12..14].copy_from_slice(&540u16.to_le_bytes());
+
+        daily_window.data[14..16].copy_from_slice(&1020u16.to_le_bytes());
+
+        daily_window.data[16] = 0b1;
+
+        daily_window.data[17..19].copy_from_slice(&1u16.to_le_bytes());
+
+        let mut usage_window = UsageWindow::zeroed();
+
+        assert_eq!(daily_window.update(&context(MONDAY + 539 * MINUTE), &mut usage_window), 
+            error(WrapperError::TooEarly));
+
+        assert_eq!(daily_window.update(&context(MONDAY + 540 * MINUTE), &mut usage_window), Ok(()));
+
+        assert_eq!(daily_window.update(&context(MONDAY + 600 * MINUTE), &mut usage_window), 
+            error(WrapperError::CountExhausted));
+
+        assert_eq!(daily_window.update(&context(MONDAY + 1020 * MINUTE), &mut usage_window), 
+            error(WrapperError::TooLate));
+
+        // Tuesday is not in the weekday mask
+        assert_eq!(daily_window.update(&context(MONDAY + SECONDS_PER_DAY + 600 * MINUTE), 
+            &mut usage_window), error(WrapperError::TooEarly));
+
+        // The count is refilled on the next Monday
+        assert_eq!(daily_window.update(&context(MONDAY + DAYS_PER_WEEK * SECONDS_PER_DAY + 600 * MINUTE), 
+            &mut usage_window), Ok(()));
+    }
+
+    #[test]
+    fn capped_repeat_count_stops_at_the_lifetime_count() {
+        // Two uses per window of 100 seconds from 1000, three uses over the lifetime
+        let mut capped_repeat = condition(CommitConditionTag::CappedRepeatCountBetweenTimestamp, 
+            2 | (2 << HALF_COUNT_BITS), 1000, 100, 3);
+
+        let mut usage_window = UsageWindow::zeroed();
+
+        assert_eq!(capped_repeat.update(&context(1000), &mut usage_window), Ok(()));
+
+        assert_eq!(capped_repeat.update(&context(1050), &mut usage_window), Ok(()));
+
+        assert_eq!(capped_repeat.update(&context(1060), &mut usage_window), 
+            error(WrapperError::CountExhausted));
+
+        // The window is refilled but only one use is left over the lifetime
+        assert_eq!(capped_repeat.update(&context(1200), &mut usage_window), Ok(()));
+
+        assert_eq!(capped_repeat.update(&context(1210), &mut usage_window), 
+            error(WrapperError::CountExhausted));
+
+        assert_eq!(capped_repeat.update(&context(1500), &mut usage_window), 
+            error(WrapperError::CountExhausted));
+
+        assert_eq!(capped_repeat.is_expired(&context(1500).clock), Ok(true));
+    }
+}
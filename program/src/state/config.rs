@@ -3,22 +3,34 @@ use bytemuck::{
     Zeroable
 };
 use pinocchio::{
-    log::sol_log_slice, program_error::ProgramError, pubkey::Pubkey
+    account_info::AccountInfo, log::sol_log_slice, program_error::ProgramError, pubkey::{self, Pubkey}, sysvars::{
+        clock::Clock, 
+        Sysvar
+    }
 };
 use crate::{
-    WrapperError, constants::{COMMIT_SEEDS_LEN, CONFIG_MAX_ACCOUNTS, CONFIG_MAX_SIGNERS, HASH_LENGTH}, state::condition::CommitCondition, utils::hashv
+    WrapperError, constants::{ACCOUNT_FLAG_SIGNER, ACCOUNT_FLAG_WRITABLE, COMMIT_SEEDS_LEN, CONFIG_MAX_SUB_CONDITIONS, CONFIG_OPTION_CONDITION_ACCOUNT, CONFIG_OPTION_LAMPORTS_OUT, CONFIG_OPTION_USAGE_WINDOW, CONFIG_OPTION_VALUE_BUDGET, HASH_LENGTH, MAX_BUNDLE_LENGTH}, state::{account_constraint::{AccountDataAssertion, AccountInvariant, AccountInvariantState, AccountOwnerConstraint, AccountPdaConstraint, AccountSet, PdaSeed, PdaSeedType}, budget::{ValueBudget, ValueBudgetTag}, form::FormUsage, constraint::{FieldConstraint, ValueSetConstraint}, condition::{CommitCondition, ConditionContext, UsageWindow}}, utils::hashv
 };
 
+extern crate alloc;
+
+use alloc::vec::Vec;
+
 type HashType = [u8;32];
 
 /// Stores the instruction data commit type for
 /// a commit, it can either be `NoData`, in that no 
 /// data is commited to, `Data`, in that some specific
-/// data is commited to and `AnyData` in that any data
-/// is valid. 
+/// data is commited to, `AnyData` in that any data
+/// is valid, `Masked` in that the bytes set in a mask
+/// must match a template and `Prefix` in that some specific
+/// data is commited to in a prefix followed by a tail of
+/// any length up to a maximum. 
 /// NoData => 0
 /// Data => 1
 /// AnyData => 2
+/// Masked => 3
+/// Prefix => 4
 #[repr(C, packed)]
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
 pub struct DataCommitType{
@@ -29,10 +41,14 @@ pub struct DataCommitType{
 /// NoData => 0
 /// Data => 1
 /// AnyData => 2
+/// Masked => 3
+/// Prefix => 4
 pub enum DataCommitTypeEnum{
     NoData,
     Data,
-    AnyData
+    AnyData,
+    Masked,
+    Prefix
 }
 
 
@@ -42,6 +58,8 @@ impl From<DataCommitTypeEnum> for DataCommitType {
             DataCommitTypeEnum::NoData => 0,
             DataCommitTypeEnum::Data => 1,
             DataCommitTypeEnum::AnyData => 2,
+            DataCommitTypeEnum::Masked => 3,
+            DataCommitTypeEnum::Prefix => 4,
         };
         DataCommitType { commit_type: [byte] }
     }
@@ -54,6 +72,8 @@ impl TryFrom<DataCommitType> for DataCommitTypeEnum {
             [0] => Ok(DataCommitTypeEnum::NoData),
             [1] => Ok(DataCommitTypeEnum::Data),
             [2] => Ok(DataCommitTypeEnum::AnyData),
+            [3] => Ok(DataCommitTypeEnum::Masked),
+            [4] => Ok(DataCommitTypeEnum::Prefix),
             _ => Err(WrapperError::InvalidDataCommitType),
         }
     }
@@ -61,32 +81,648 @@ impl TryFrom<DataCommitType> for DataCommitTypeEnum {
     type Error = WrapperError;
 }
 
-/// Stores information about the configuration as well as the
-/// signers and the starter
+/// Stores a byte range of the instruction data that is committed to
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct DataRange{
+    pub offset: [u8; 2],
+    pub length: [u8; 2]
+}
+
+impl DataRange{
+    /// Gets the start and end of the range
+    #[inline(always)]
+    pub fn bounds(&self) -> (usize, usize) {
+        let offset = usize::from(u16::from_le_bytes(self.offset));
+        let length = usize::from(u16::from_le_bytes(self.length));
+
+        (offset, offset + length)
+    }
+}
+
+/// Stores information about the configuration as well as the starter of
+/// the commit, it is the fixed part of the commit account and is followed by
+/// the state and options of the configuration, it's sections, the signer keys,
+/// the usage table of a forms commit and the usage window:-
+///
+/// Config -- ConfigState -- ConfigOptions -- ConfigSections -- [Signer keys] -- 
+/// [Form usage] -- [Usage window]
 #[repr(C, packed)]
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
 pub struct Config{
     pub base:ConfigBase,
-    pub starter_key:Pubkey,
-    pub signer_keys: [Pubkey; CONFIG_MAX_SIGNERS]
+    pub starter_key:Pubkey
 }
 
 impl Config{
     pub const LEN:usize = core::mem::size_of::<Config>();
 }
 
-impl ConfigBase{
-    pub const LEN:usize = core::mem::size_of::<ConfigBase>();
+/// Stores the parts of a commit account, see Config::load
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigData<'a>{
+    pub config: &'a Config,
+    pub state: ConfigState<'a>,
+    pub options: ConfigOptions<'a>,
+    pub sections: ConfigSections<'a>,
+    pub signer_keys: &'a [Pubkey],
+}
+
+/// Stores the parts of a commit account with the ones changed when the commit
+/// is used being mutable, see Config::load_mut
+#[derive(Debug)]
+pub struct ConfigDataMut<'a>{
+    pub config: &'a mut Config,
+    pub state: ConfigStateMut<'a>,
+    pub options: ConfigOptions<'a>,
+    pub sections: ConfigSections<'a>,
+    pub signer_keys: &'a [Pubkey],
+    pub form_usage: &'a mut [FormUsage],
+    pub usage_window: Option<&'a mut UsageWindow>,
+}
+
+impl Config{
+    /// Splits the data of the commit account into it's parts, the data must be exactly
+    /// as long as the counts and options in the header require
+    pub fn load(data:&[u8])->Result<ConfigData<'_>, ProgramError>{
+        if data.len().lt(&Config::LEN){
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (config, rest) = data.split_at(Config::LEN);
+
+        let config = bytemuck::try_from_bytes::<Config>(config).
+            map_err(|_| ProgramError::InvalidAccountData)?;
+
+        // The usage table of a forms commit and the usage window come after the signer keys
+        let tail_len = config.base.get_form_usage_len() + config.base.get_usage_window_len();
+
+        if rest.len().lt(&tail_len){
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (rest, _) = rest.split_at(rest.len() - tail_len);
+
+        let (state, rest) = ConfigState::try_from_bytes(&config.base, rest)?;
+
+        let (options, rest) = ConfigOptions::try_from_bytes(&config.base, rest)?;
+
+        let (sections, signer_keys) = Self::load_sections(&config.base, rest)?;
+
+        Ok(ConfigData{ config, state, options, sections, signer_keys })
+    }
+
+    /// Same as Config::load but the fixed part and state are mutable, the usage table
+    /// of a forms commit and the usage window are also returned
+    pub fn load_mut(data:&mut [u8])->Result<ConfigDataMut<'_>, ProgramError>{
+        if data.len().lt(&Config::LEN){
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (config, rest) = data.split_at_mut(Config::LEN);
+
+        let config = bytemuck::try_from_bytes_mut::<Config>(config).
+            map_err(|_| ProgramError::InvalidAccountData)?;
+
+        // The usage table of a forms commit and the usage window come after the signer keys
+        let form_usage_len = config.base.get_form_usage_len();
+
+        let usage_window_len = config.base.get_usage_window_len();
+
+        if rest.len().lt(&(form_usage_len + usage_window_len)){
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let rest_len = rest.len();
+
+        let (rest, usage_window) = rest.split_at_mut(rest_len - usage_window_len);
+
+        let usage_window = bytemuck::try_cast_slice_mut::<u8, UsageWindow>(usage_window).
+            map_err(|_| ProgramError::InvalidAccountData)?.first_mut();
+
+        let rest_len = rest.len();
+
+        let (rest, form_usage) = rest.split_at_mut(rest_len - form_usage_len);
+
+        let form_usage = bytemuck::try_cast_slice_mut::<u8, FormUsage>(form_usage).
+            map_err(|_| ProgramError::InvalidAccountData)?;
+
+        let (state, rest) = ConfigStateMut::try_from_bytes_mut(&config.base, rest)?;
+
+        let (options, rest) = ConfigOptions::try_from_bytes(&config.base, rest)?;
+
+        let (sections, signer_keys) = Self::load_sections(&config.base, rest)?;
+
+        Ok(ConfigDataMut{ config, state, options, sections, signer_keys, form_usage, usage_window })
+    }
+
+    /// Extracts the sections and the signer keys that follow the options
+    #[inline(always)]
+    fn load_sections<'a>(base:&ConfigBase, data:&'a [u8])->Result<(ConfigSections<'a>, &'a [Pubkey]), ProgramError>{
+        let (sections, signer_keys) = ConfigSections::try_from_bytes(&base.form, data)?;
+
+        let signer_keys = bytemuck::try_cast_slice::<u8, Pubkey>(signer_keys).
+            map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if signer_keys.len().ne(&usize::from(base.form.signer_count[0])){
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok((sections, signer_keys))
+    }
 }
 
 impl ConfigBase{
+    /// Gets the length of a commit account with the given length of the state, options
+    /// and sections
+    #[inline(always)]
+    pub fn get_account_len(&self, config_data_len:usize)->usize{
+        Config::LEN + config_data_len + 
+            usize::from(self.form.signer_count[0]) * core::mem::size_of::<Pubkey>() +
+            self.get_form_usage_len() + self.get_usage_window_len()
+    }
+}
+
+/// Stores the state of the configuration that is changed when the commit is used or
+/// it's condition is changed, the sub-condition count and options in the base set which
+/// parts are stored so commits only store what they use, they are laid out in this order:-
+///
+/// sub_conditions:- [CommitCondition; sub_condition_count]
+/// value_budget:- [ValueBudget](only with the value budget option)
+/// condition_account_key:- [Pubkey](only with the condition account option)
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigState<'a>{
+    pub sub_conditions: &'a [CommitCondition],
+    pub value_budget: Option<&'a ValueBudget>,
+    pub condition_account_key: Option<&'a Pubkey>,
+}
+
+impl<'a> ConfigState<'a>{
+    /// Extracts the state from the start of the data using the sub-condition count and
+    /// options in the base, returns the state along with the rest of the data
+    pub fn try_from_bytes(base:&ConfigBase, data:&'a [u8])->Result<(Self, &'a [u8]), ProgramError>{
+        let mut data = data;
+
+        let state = ConfigState{
+            sub_conditions: 
+                ConfigSections::split_section(&mut data, usize::from(base.sub_condition_count[0]))?,
+            value_budget: ConfigSections::split_section(&mut data, 
+                usize::from(base.has_option(CONFIG_OPTION_VALUE_BUDGET)))?.first(),
+            condition_account_key: ConfigSections::split_section(&mut data, 
+                usize::from(base.has_option(CONFIG_OPTION_CONDITION_ACCOUNT)))?.first(),
+        };
+
+        Ok((state, data))
+    }
+}
+
+/// Same as ConfigState but mutable
+#[derive(Debug)]
+pub struct ConfigStateMut<'a>{
+    pub sub_conditions: &'a mut [CommitCondition],
+    pub value_budget: Option<&'a mut ValueBudget>,
+    pub condition_account_key: Option<&'a mut Pubkey>,
+}
+
+impl<'a> ConfigStateMut<'a>{
+    /// Same as ConfigState::try_from_bytes but the state is mutable
+    pub fn try_from_bytes_mut(base:&ConfigBase, data:&'a mut [u8])->Result<(Self, &'a mut [u8]), ProgramError>{
+        let mut data = data;
+
+        let state = ConfigStateMut{
+            sub_conditions: 
+                Self::split_section_mut(&mut data, usize::from(base.sub_condition_count[0]))?,
+            value_budget: Self::split_section_mut(&mut data, 
+                usize::from(base.has_option(CONFIG_OPTION_VALUE_BUDGET)))?.first_mut(),
+            condition_account_key: Self::split_section_mut(&mut data, 
+                usize::from(base.has_option(CONFIG_OPTION_CONDITION_ACCOUNT)))?.first_mut(),
+        };
+
+        Ok((state, data))
+    }
+
+    /// Splits a mutable section of `count` items off the front of the data
+    #[inline(always)]
+    fn split_section_mut<T:Pod>(data:&mut &'a mut [u8], count:usize)->Result<&'a mut [T], ProgramError>{
+        let length = count * core::mem::size_of::<T>();
+
+        if data.len().lt(&length){
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (section, rest) = core::mem::take(data).split_at_mut(length);
+
+        *data = rest;
+
+        bytemuck::try_cast_slice_mut::<u8, T>(section).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+/// Stores the bundle of a bundle commit, the budget mask sets the instructions that
+/// spend the value budget(bit i for instruction i) and the hash binds the forms of
+/// the instructions in the order they are executed
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct ConfigBundle{
+    pub budget_mask: [u8;1],
+    pub hash: HashType,
+}
+
+/// Stores the options of the configuration that are set when the commit is created,
+/// they are only stored for the commits that use them and are laid out in this order:-
+///
+/// max_lamports_out:- [u64](only with the lamports out option, otherwise the outflow
+/// is uncapped)
+/// form_root:- [HashType](only for a forms commit)
+/// bundle:- [ConfigBundle](only for a bundle commit)
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigOptions<'a>{
+    pub max_lamports_out: Option<&'a [u8;8]>,
+    pub form_root: Option<&'a HashType>,
+    pub bundle: Option<&'a ConfigBundle>,
+}
+
+impl<'a> ConfigOptions<'a>{
+    /// Extracts the options from the start of the data using the options, form leaf count
+    /// and bundle length in the base, returns the options along with the rest of the data
+    pub fn try_from_bytes(base:&ConfigBase, data:&'a [u8])->Result<(Self, &'a [u8]), ProgramError>{
+        let mut data = data;
+
+        let options = ConfigOptions{
+            max_lamports_out: ConfigSections::split_section(&mut data, 
+                usize::from(base.has_option(CONFIG_OPTION_LAMPORTS_OUT)))?.first(),
+            form_root: ConfigSections::split_section(&mut data, 
+                usize::from(base.is_forms_commit()))?.first(),
+            bundle: ConfigSections::split_section(&mut data, 
+                usize::from(base.is_bundle_commit()))?.first(),
+        };
+
+        Ok((options, data))
+    }
+
+    /// Checks if the lamport cap is valid, a cap of u64::MAX is the same as no cap so
+    /// the option must be left unset instead
+    pub fn is_max_lamports_out_valid(&self)->Result<(), WrapperError>{
+        if self.max_lamports_out.is_some_and(|max_lamports_out| 
+            u64::from_le_bytes(*max_lamports_out).eq(&u64::MAX)){
+            return Err(WrapperError::InvalidMaxLamportsOut);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the value budget is spent by the instruction at the index, for a bundle
+    /// commit these are the instructions in the budget mask, otherwise the only instruction
+    #[inline(always)]
+    pub fn is_budgeted_instruction(&self, index:usize)->bool{
+        let Some(bundle) = self.bundle else{
+            return index.eq(&0);
+        };
+
+        u32::try_from(index).ok().
+            and_then(|index| bundle.budget_mask[0].checked_shr(index)).
+            is_some_and(|mask| (mask & 1).eq(&1))
+    }
+}
+
+/// Stores the variable length sections of the configuration, their lengths are
+/// set by the counts in the form so commits only store what they use, the sections
+/// are laid out one after another in this order:-
+///
+/// account_indices:- [u8; account_count]
+/// account_flags:- [u8; account_flag_count](either zero or account_count)
+/// account_sets:- [AccountSet; account_set_count]
+/// account_set_keys:- [Pubkey; total key_count of the account sets]
+/// account_owner_constraints:- [AccountOwnerConstraint; account_owner_constraint_count]
+/// account_pda_constraints:- [AccountPdaConstraint; account_pda_constraint_count]
+/// account_pda_seeds:- [PdaSeed; total seed_count of the PDA constraints]
+/// signer_bumps:- [u8; signer_count]
+/// instruction_data_ranges:- [DataRange; instruction_data_range_count]
+/// instruction_data_template:- [u8; instruction_data_length](only for `Masked`)
+/// instruction_data_mask:- [u8; instruction_data_length](only for `Masked`)
+/// field_constraints:- [FieldConstraint; field_constraint_count]
+/// value_set_constraints:- [ValueSetConstraint; value_set_constraint_count]
+/// account_data_assertions:- [AccountDataAssertion; account_data_assertion_count]
+/// account_invariants:- [AccountInvariant; account_invariant_count]
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigSections<'a>{
+    pub account_indices: &'a [u8],
+    pub account_flags: &'a [u8],
+    pub account_sets: &'a [AccountSet],
+    pub account_set_keys: &'a [Pubkey],
+    pub account_owner_constraints: &'a [AccountOwnerConstraint],
+    pub account_pda_constraints: &'a [AccountPdaConstraint],
+    pub account_pda_seeds: &'a [PdaSeed],
+    pub signer_bumps: &'a [u8],
+    pub instruction_data_ranges: &'a [DataRange],
+    pub instruction_data_template: &'a [u8],
+    pub instruction_data_mask: &'a [u8],
+    pub field_constraints: &'a [FieldConstraint],
+    pub value_set_constraints: &'a [ValueSetConstraint],
+    pub account_data_assertions: &'a [AccountDataAssertion],
+    pub account_invariants: &'a [AccountInvariant],
+}
+
+impl<'a> ConfigSections<'a>{
+    /// Extracts the sections from the start of the data using the counts in the form,
+    /// returns the sections along with the rest of the data
+    pub fn try_from_bytes(form:&CommitForm, data:&'a [u8])->Result<(Self, &'a [u8]), ProgramError>{
+        let mut data = data;
+
+        let mask_length = form.get_mask_length();
+
+        let account_indices = Self::split_section(&mut data, usize::from(form.account_count[0]))?;
+
+        let account_flags = Self::split_section(&mut data, usize::from(form.account_flag_count[0]))?;
+
+        let account_sets:&[AccountSet] = 
+            Self::split_section(&mut data, usize::from(form.account_set_count[0]))?;
+
+        // The keys of each inline set are stored one after another
+        let account_set_key_count:usize = account_sets.iter().
+            map(|account_set| usize::from(account_set.key_count)).sum();
+
+        let account_set_keys = Self::split_section(&mut data, account_set_key_count)?;
+
+        let account_owner_constraints = 
+            Self::split_section(&mut data, usize::from(form.account_owner_constraint_count[0]))?;
+
+        let account_pda_constraints:&[AccountPdaConstraint] = 
+            Self::split_section(&mut data, usize::from(form.account_pda_constraint_count[0]))?;
+
+        // The seeds of each PDA constraint are stored one after another
+        let account_pda_seed_count:usize = account_pda_constraints.iter().
+            map(|constraint| usize::from(constraint.seed_count)).sum();
+
+        let account_pda_seeds = Self::split_section(&mut data, account_pda_seed_count)?;
+
+        let sections = ConfigSections{
+            account_indices,
+            account_flags,
+            account_sets,
+            account_set_keys,
+            account_owner_constraints,
+            account_pda_constraints,
+            account_pda_seeds,
+            signer_bumps: Self::split_section(&mut data, usize::from(form.signer_count[0]))?,
+            instruction_data_ranges: 
+                Self::split_section(&mut data, usize::from(form.instruction_data_range_count[0]))?,
+            instruction_data_template: Self::split_section(&mut data, mask_length)?,
+            instruction_data_mask: Self::split_section(&mut data, mask_length)?,
+            field_constraints: 
+                Self::split_section(&mut data, usize::from(form.field_constraint_count[0]))?,
+            value_set_constraints: 
+                Self::split_section(&mut data, usize::from(form.value_set_constraint_count[0]))?,
+            account_data_assertions: 
+                Self::split_section(&mut data, usize::from(form.account_data_assertion_count[0]))?,
+            account_invariants: 
+                Self::split_section(&mut data, usize::from(form.account_invariant_count[0]))?,
+        };
+
+        Ok((sections, data))
+    }
+
+    /// Checks if the bytes of the data set in the mask match the template, the
+    /// length is expected to have been checked against the template length
+    #[inline(always)]
+    fn data_matches_mask(&self, data:&[u8])->bool{
+        data.iter().zip(self.instruction_data_template.iter()).
+            zip(self.instruction_data_mask.iter()).
+            all(|((data_byte, template_byte), mask_byte)| 
+                (data_byte & mask_byte).eq(&(template_byte & mask_byte)))
+    }
+
+    /// Gets the writable and signer flags of the account
+    #[inline(always)]
+    fn get_account_flags(account:&AccountInfo)->u8{
+        let mut flags:u8 = 0;
+
+        if account.is_writable(){
+            flags |= ACCOUNT_FLAG_WRITABLE;
+        }
+
+        if account.is_signer(){
+            flags |= ACCOUNT_FLAG_SIGNER;
+        }
+
+        flags
+    }
+
+    /// Checks if the writable and signer flags of the accounts passed to the program match the
+    /// flags committed to for their positions, the accounts at positions that are not committed
+    /// to can only have the flags set in the other account flags, no check is made when the
+    /// flags are not committed to
+    pub fn check_account_flags(&self, other_account_flags:u8, accounts:&[AccountInfo])->Result<(), ProgramError>{
+        if self.account_flags.is_empty(){
+            return Ok(());
+        }
+
+        for (position, account) in accounts.iter().enumerate(){
+            let flags = Self::get_account_flags(account);
+
+            let expected_flags = self.account_indices.iter().
+                position(|index| usize::from(*index).eq(&position)).
+                map(|index| self.account_flags[index]);
+
+            let is_allowed = match expected_flags {
+                Some(expected_flags) => flags.eq(&expected_flags),
+                None => (flags & !other_account_flags).eq(&0),
+            };
+
+            if !is_allowed{
+                return Err(WrapperError::AccountFlagsMismatch.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the key of the account at the committed position is not pinned, that is when
+    /// it is bound to an account set, an owner constraint or a PDA constraint, the key of the
+    /// account at such a position is not part of the accounts hash
+    #[inline(always)]
+    pub fn is_unpinned_position(&self, position:usize)->bool{
+        self.account_sets.iter().map(|account_set| account_set.position).
+            chain(self.account_owner_constraints.iter().map(|constraint| constraint.position)).
+            chain(self.account_pda_constraints.iter().map(|constraint| constraint.position)).
+            any(|unpinned_position| usize::from(unpinned_position).eq(&position))
+    }
+
+    /// Checks if the mask of a `Masked` commit sets any byte, a mask that sets no bytes
+    /// would commit to nothing
+    #[inline(always)]
+    pub fn is_data_mask_set(&self)->bool{
+        self.instruction_data_mask.iter().any(|mask_byte| mask_byte.ne(&0))
+    }
+
+    /// Gets the account at the committed position from the accounts passed to the program
+    #[inline(always)]
+    fn get_position_account<'b>(&self, accounts:&'b [AccountInfo], position:u8)->Result<&'b AccountInfo, ProgramError>{
+        let index = self.account_indices.get(usize::from(position)).
+            ok_or(ProgramError::InvalidAccountData)?;
+
+        accounts.get(usize::from(*index)).ok_or(ProgramError::NotEnoughAccountKeys)
+    }
+
+    /// Checks if the accounts at the positions bound to owner constraints meet them
+    pub fn check_account_owner_constraints(&self, accounts:&[AccountInfo])->Result<(), ProgramError>{
+        for constraint in self.account_owner_constraints{
+            constraint.check(self.get_position_account(accounts, constraint.position)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the data of the accounts at the positions bound to assertions holds them
+    pub fn check_account_data_assertions(&self, accounts:&[AccountInfo])->Result<(), ProgramError>{
+        for assertion in self.account_data_assertions{
+            assertion.check(self.get_position_account(accounts, assertion.position)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the states of the accounts at the positions bound to invariants, they are taken
+    /// before the program is invoked in the order of the invariants
+    pub fn get_account_invariant_states(&self, accounts:&[AccountInfo])->Result<Vec<AccountInvariantState>, ProgramError>{
+        self.account_invariants.iter().
+            map(|invariant| invariant.get_state(self.get_position_account(accounts, invariant.position)?)).
+            collect()
+    }
+
+    /// Checks if the states of the accounts at the positions bound to invariants are the
+    /// same as the ones taken before the program was invoked
+    pub fn check_account_invariants(&self, accounts:&[AccountInfo], states:&[AccountInvariantState])->Result<(), ProgramError>{
+        for (invariant, state) in self.account_invariants.iter().zip(states.iter()){
+            invariant.check(self.get_position_account(accounts, invariant.position)?, state)?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the length of the proofs of the `Merkle` account sets, they are passed after the
+    /// instruction data of the program when invoking
+    #[inline(always)]
+    pub fn get_account_set_proofs_len(&self)->usize{
+        self.account_sets.iter().map(|account_set| account_set.get_proof_len()).sum()
+    }
+
+    /// Checks if the accounts at the positions bound to account sets are members of their sets,
+    /// the proofs are taken in the order of the account sets
+    pub fn check_account_sets(&self, accounts:&[AccountInfo], proofs:&[u8])->Result<(), ProgramError>{
+        if proofs.len().ne(&self.get_account_set_proofs_len()){
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let mut keys = self.account_set_keys;
+
+        let mut proofs = proofs;
+
+        for account_set in self.account_sets{
+            let (set_keys, other_keys) = keys.split_at(usize::from(account_set.key_count));
+
+            let (proof, other_proofs) = proofs.split_at(account_set.get_proof_len());
+
+            keys = other_keys;
+
+            proofs = other_proofs;
+
+            let account = self.get_position_account(accounts, account_set.position)?;
+
+            if !account_set.contains(account.key(), set_keys, proof)?{
+                return Err(WrapperError::AccountNotInSet.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the accounts at the positions bound to PDA constraints are derived as committed
+    /// to, the seeds are built from the constants and the keys of the accounts at the other
+    /// committed positions followed by the bump from the instruction data
+    pub fn check_account_pda_constraints(&self, accounts:&[AccountInfo], data:&[u8])->Result<(), ProgramError>{
+        let mut seeds = self.account_pda_seeds;
+
+        for constraint in self.account_pda_constraints{
+            let (constraint_seeds, other_seeds) = seeds.split_at(usize::from(constraint.seed_count));
+
+            seeds = other_seeds;
+
+            let mut seed_values:Vec<&[u8]> = Vec::with_capacity(constraint_seeds.len() + 1);
+
+            for seed in constraint_seeds{
+                let seed_value = match PdaSeedType::try_from(seed.seed_type)? {
+                    PdaSeedType::Constant => &seed.value[..usize::from(seed.length)],
+                    PdaSeedType::AccountKey => 
+                        self.get_position_account(accounts, seed.position)?.key().as_slice(),
+                };
+
+                seed_values.push(seed_value);
+            }
+
+            let bump_offset = usize::from(u16::from_le_bytes(constraint.bump_offset));
+
+            seed_values.push(data.get(bump_offset..(bump_offset + 1)).
+                ok_or(ProgramError::InvalidInstructionData)?);
+
+            let expected_account = pubkey::create_program_address(&seed_values, &constraint.program).
+                map_err(|_| WrapperError::PdaConstraintNotMet)?;
+
+            if self.get_position_account(accounts, constraint.position)?.key().ne(&expected_account){
+                return Err(WrapperError::PdaConstraintNotMet.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the fields of the instruction data are within the bounds of the
+    /// field constraints and are one of the values of the value set constraints
+    pub fn check_field_constraints(&self, data:&[u8])->Result<(), ProgramError>{
+        for constraint in self.field_constraints{
+            constraint.check(data)?;
+        }
+
+        for value_set in self.value_set_constraints{
+            value_set.check(data)?;
+        }
+
+        Ok(())
+    }
+
+    /// Splits a section of `count` items off the front of the data
+    #[inline(always)]
+    fn split_section<T:Pod>(data:&mut &'a [u8], count:usize)->Result<&'a [T], ProgramError>{
+        let length = count * core::mem::size_of::<T>();
+
+        if data.len().lt(&length){
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (section, rest) = data.split_at(length);
+
+        *data = rest;
+
+        bytemuck::try_cast_slice::<u8, T>(section).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl CommitForm{
+    pub const LEN:usize = core::mem::size_of::<CommitForm>();
+}
+
+impl CommitForm{
     /// Checks whether the provided instruction data length matches the commit type
     /// recorded in this configuration.
     ///
     /// - `NoData`: length must be exactly zero.
     /// - `AnyData`: any length is accepted.
-    /// - `Data`: length must match the committed instruction data length stored
-    ///   in this configuration.
+    /// - `Data`: length must match the total instruction data length stored
+    ///   in this configuration, the committed byte ranges are within it.
+    /// - `Masked`: length must match the length of the template stored in
+    ///   this configuration.
+    /// - `Prefix`: length must be at least the length of the prefix and at most
+    ///   the maximum length stored in this configuration.
     pub fn length_matches_commit_type(&self, length: usize) -> bool {
 
         // Extract enum from the commit type
@@ -97,38 +733,302 @@ impl ConfigBase{
         let committed_length =
             u16::from_le_bytes(self.instruction_data_length) as usize;
 
+        let committed_max_length =
+            u16::from_le_bytes(self.instruction_data_max_length) as usize;
+
         match commit_type {
             Ok(DataCommitTypeEnum::NoData) => length == 0,
             Ok(DataCommitTypeEnum::AnyData) => true,
-            Ok(DataCommitTypeEnum::Data) => length == committed_length,
+            Ok(DataCommitTypeEnum::Data) | 
+            Ok(DataCommitTypeEnum::Masked) => length == committed_length,
+            Ok(DataCommitTypeEnum::Prefix) => 
+                length >= committed_length && length <= committed_max_length,
             Err(_) => false, // Invalid commit type
         }
     }
 
-    /// Updates the commit condition, see CommitCondition::update for more
-    /// information
+    /// Gets the length of the template and the mask, they are only stored for `Masked`
+    #[inline(always)]
+    pub fn get_mask_length(&self) -> usize {
+        match DataCommitTypeEnum::try_from(self.instruction_data_commit_type) {
+            Ok(DataCommitTypeEnum::Masked) => 
+                usize::from(u16::from_le_bytes(self.instruction_data_length)),
+            _ => 0,
+        }
+    }
+
+    /// Checks if the committed byte ranges are valid for the data commit type, when the
+    /// type is `Data` or `Prefix` there must be at least one range, the ranges must be non
+    /// empty, in ascending order, not overlap and be within the instruction data length(the
+    /// prefix length), likewise the maximum length is only set for `Prefix`
+    pub fn is_data_commit_valid(&self, sections:&ConfigSections)->Result<(), WrapperError>{
+        let range_count = sections.instruction_data_ranges.len();
+
+        let instruction_data_max_length = u16::from_le_bytes(self.instruction_data_max_length);
+
+        match DataCommitTypeEnum::try_from(self.instruction_data_commit_type)? {
+            DataCommitTypeEnum::Prefix => {
+                if range_count.eq(&0) || 
+                    instruction_data_max_length.lt(&u16::from_le_bytes(self.instruction_data_length)){
+                    return Err(WrapperError::InvalidDataRange);
+                }
+            },
+            _ if instruction_data_max_length.ne(&0) => {
+                return Err(WrapperError::InvalidDataRange);
+            },
+            DataCommitTypeEnum::Data => {
+                if range_count.eq(&0){
+                    return Err(WrapperError::InvalidDataRange);
+                }
+            },
+            DataCommitTypeEnum::NoData | DataCommitTypeEnum::AnyData => {
+                if range_count.ne(&0){
+                    return Err(WrapperError::InvalidDataRange);
+                }
+            },
+            DataCommitTypeEnum::Masked => {
+                if range_count.ne(&0){
+                    return Err(WrapperError::InvalidDataRange);
+                }
+
+                self.is_data_mask_valid(sections)?;
+            }
+        }
+
+        let instruction_data_length = usize::from(u16::from_le_bytes(self.instruction_data_length));
+
+        let mut previous_end:usize = 0;
+
+        for range in sections.instruction_data_ranges.iter(){
+            let (offset, end) = range.bounds();
+
+            if offset.ge(&end) || offset.lt(&previous_end) || end.gt(&instruction_data_length){
+                return Err(WrapperError::InvalidDataRange);
+            }
+
+            previous_end = end;
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the hash is that of the masked template followed by the mask, the hash
+    /// is used as a seed so it binds the commit account to the template and mask, their
+    /// lengths are set by the instruction data length, a mask that sets no bytes would
+    /// commit to nothing so it is rejected
+    fn is_data_mask_valid(&self, sections:&ConfigSections)->Result<(), WrapperError>{
+        if !sections.is_data_mask_set(){
+            return Err(WrapperError::InvalidDataMask);
+        }
+
+        let masked_template:Vec<u8> = sections.instruction_data_template.iter().
+            zip(sections.instruction_data_mask.iter()).
+            map(|(template_byte, mask_byte)| template_byte & mask_byte).collect();
+
+        let mut mask_hash:[u8;HASH_LENGTH] = [0;HASH_LENGTH];
+
+        hashv(&[masked_template.as_slice(), sections.instruction_data_mask], &mut mask_hash);
+
+        if mask_hash.ne(&self.instruction_data_hash){
+            return Err(WrapperError::InvalidDataMask);
+        }
+
+        Ok(())
+    }
+
+    /// Gets the length the instruction data can have at most for the data commit type,
+    /// it is not bounded when any data is valid
     #[inline(always)]
-    pub fn update_condition(&mut self)->Result<(), ProgramError>{
-        self.condition.update()
+    pub fn get_max_instruction_data_length(&self)->Result<Option<usize>, WrapperError>{
+        let instruction_data_length = 
+            match DataCommitTypeEnum::try_from(self.instruction_data_commit_type)? {
+                DataCommitTypeEnum::NoData => Some(0),
+                DataCommitTypeEnum::Data | DataCommitTypeEnum::Masked => 
+                    Some(usize::from(u16::from_le_bytes(self.instruction_data_length))),
+                DataCommitTypeEnum::Prefix => 
+                    Some(usize::from(u16::from_le_bytes(self.instruction_data_max_length))),
+                DataCommitTypeEnum::AnyData => None,
+            };
+
+        Ok(instruction_data_length)
+    }
+
+    /// Checks if the field and value set constraints are valid, the fields must be within the
+    /// instruction data when it's length is bounded
+    pub fn is_field_constraints_valid(&self, sections:&ConfigSections)->Result<(), WrapperError>{
+        let instruction_data_length = self.get_max_instruction_data_length()?;
+
+        for constraint in sections.field_constraints{
+            constraint.is_valid(instruction_data_length)?;
+        }
+
+        for value_set in sections.value_set_constraints{
+            value_set.is_valid(instruction_data_length)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the account flags are valid, they are either not committed to or there is one for
+    /// each committed account, only the writable and signer bits can be set
+    pub fn is_account_flags_valid(&self, sections:&ConfigSections)->Result<(), WrapperError>{
+        let valid_flags = ACCOUNT_FLAG_WRITABLE | ACCOUNT_FLAG_SIGNER;
+
+        if !sections.account_flags.is_empty() && 
+            sections.account_flags.len().ne(&sections.account_indices.len()){
+            return Err(WrapperError::InvalidAccountFlags);
+        }
+
+        if sections.account_flags.iter().chain(self.other_account_flags.iter()).
+            any(|flags| (flags & !valid_flags).ne(&0)){
+            return Err(WrapperError::InvalidAccountFlags);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the account sets are valid, they must be in ascending order of their positions
+    /// with at most one per position and can not be at the positions of the program signers
+    /// since those are derived from the signers
+    pub fn is_account_sets_valid(&self, sections:&ConfigSections)->Result<(), WrapperError>{
+        for account_set in sections.account_sets{
+            account_set.is_valid()?;
+        }
+
+        if !self.is_unpinned_positions_valid(sections.account_sets.iter().
+            map(|account_set| account_set.position)){
+            return Err(WrapperError::InvalidAccountSet);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the account owner constraints are valid, they must be in ascending order of
+    /// their positions with at most one per position and can not be at the positions of the
+    /// program signers
+    pub fn is_account_owner_constraints_valid(&self, sections:&ConfigSections)->Result<(), WrapperError>{
+        for constraint in sections.account_owner_constraints{
+            constraint.is_valid()?;
+        }
+
+        if !self.is_unpinned_positions_valid(sections.account_owner_constraints.iter().
+            map(|constraint| constraint.position)){
+            return Err(WrapperError::InvalidAccountOwnerConstraint);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the PDA constraints are valid, they must be in ascending order of their
+    /// positions with at most one per position and can not be at the positions of the
+    /// program signers, see AccountPdaConstraint::is_valid for more information
+    pub fn is_account_pda_constraints_valid(&self, sections:&ConfigSections)->Result<(), WrapperError>{
+        let instruction_data_length = self.get_max_instruction_data_length()?;
+
+        let mut seeds = sections.account_pda_seeds;
+
+        for constraint in sections.account_pda_constraints{
+            let (constraint_seeds, other_seeds) = seeds.split_at(usize::from(constraint.seed_count));
+
+            seeds = other_seeds;
+
+            constraint.is_valid(constraint_seeds, self.account_count[0], instruction_data_length)?;
+        }
+
+        if !self.is_unpinned_positions_valid(sections.account_pda_constraints.iter().
+            map(|constraint| constraint.position)){
+            return Err(WrapperError::InvalidPdaConstraint);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the account data assertions are valid, there can be any number of them
+    /// at any committed position, see AccountDataAssertion::is_valid for more information
+    pub fn is_account_data_assertions_valid(&self, sections:&ConfigSections)->Result<(), WrapperError>{
+        for assertion in sections.account_data_assertions{
+            assertion.is_valid(self.account_count[0])?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the account invariants are valid, there can be any number of them at
+    /// any committed position, see AccountInvariant::is_valid for more information
+    pub fn is_account_invariants_valid(&self, sections:&ConfigSections)->Result<(), WrapperError>{
+        for invariant in sections.account_invariants{
+            invariant.is_valid(self.account_count[0])?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the positions are in ascending order without repeats and are not those
+    /// of the program signers
+    fn is_unpinned_positions_valid(&self, positions:impl Iterator<Item = u8>)->bool{
+        let non_signer_count = self.account_count[0].saturating_sub(self.signer_count[0]);
+
+        let mut next_position:u8 = 0;
+
+        for position in positions{
+            if position.lt(&next_position) || position.ge(&non_signer_count){
+                return false;
+            }
+
+            next_position = position + 1;
+        }
+
+        true
+    }
+
+    /// Gets the hash of the committed byte ranges and the constraints placed on the instruction,
+    /// it is used as a seed so commits that only differ in them have different accounts, the
+    /// counts are hashed first since the sections have variable lengths
+    pub fn get_constraints_hash(&self, sections:&ConfigSections, constraints_hash:&mut [u8; HASH_LENGTH]){
+        hashv(&[self.instruction_data_range_count.as_ref(),
+            self.field_constraint_count.as_ref(), 
+            self.value_set_constraint_count.as_ref(),
+            self.account_flag_count.as_ref(),
+            self.other_account_flags.as_ref(),
+            sections.account_flags,
+            self.account_set_count.as_ref(),
+            bytemuck::cast_slice(sections.account_sets),
+            bytemuck::cast_slice(sections.account_set_keys),
+            self.account_owner_constraint_count.as_ref(),
+            bytemuck::cast_slice(sections.account_owner_constraints),
+            self.account_pda_constraint_count.as_ref(),
+            bytemuck::cast_slice(sections.account_pda_constraints),
+            bytemuck::cast_slice(sections.account_pda_seeds),
+            bytemuck::cast_slice(sections.instruction_data_ranges),
+            bytemuck::cast_slice(sections.field_constraints),
+            bytemuck::cast_slice(sections.value_set_constraints),
+            self.account_data_assertion_count.as_ref(),
+            bytemuck::cast_slice(sections.account_data_assertions),
+            self.account_invariant_count.as_ref(),
+            bytemuck::cast_slice(sections.account_invariants)], constraints_hash);
     }
 
     /// Gets the seeds used to create the commit account:-
     /// 
-    /// account_indices,
     /// signer_count,
-    /// accounts_hash,
+    /// accounts_hash(the account indices followed by the committed keys),
     /// instruction_data_commit_type,
-    /// instruction_data_offset,
+    /// instruction_data_length,
+    /// instruction_data_max_length,
     /// data_hash,
+    /// constraints_hash(the committed byte ranges and the constraints),
     /// commit_bump
     /// 
+    /// The commit bump is stored outside the form so it is passed in
+    /// 
     /// Based on the different forms that the data commitment can take different actions would need to be
-    /// performed, when the data is passed and the data commit type is `Data`, then we would need to confirm
-    /// the hash, if data is passed and the commit type is set to `NoData`, then we confirm no data is passed
+    /// performed, when the data is passed and the data commit type is `Data` or `Prefix`, then we would need to confirm
+    /// the hash of the committed byte ranges(hashed together in order), if the data commit type is `Masked`
+    /// we confirm the bytes set in the mask match the template, if data is passed and the commit type is set to `NoData`, then we confirm no data is passed
     /// if the commit type is set to `AnyData`, we we check nothing.
     /// If no data is passed we make no checks.
-    pub fn get_commit_seeds<'a, 'b>(&'a self, commit_accounts_hash:&'b[u8], 
-        maybe_data:Option<&[u8]>)->Result<[&'b[u8]; COMMIT_SEEDS_LEN], ProgramError>
+    pub fn get_commit_seeds<'a, 'b>(&'a self, sections:&ConfigSections, commit_accounts_hash:&'b[u8], 
+        constraints_hash:&'b[u8], commit_bump:&'b[u8], maybe_data:Option<&[u8]>)->Result<[&'b[u8]; COMMIT_SEEDS_LEN], ProgramError>
     where
         'a:'b,
     {
@@ -141,20 +1041,15 @@ impl ConfigBase{
 
             match DataCommitTypeEnum::try_from(self.instruction_data_commit_type)?{
                 // If some data is passed we extract it and compare it with the hash
-                DataCommitTypeEnum::Data=>{
-                    let instruction_data_offset = usize::from(u16::from_le_bytes(self.instruction_data_offset));
-                    let instruction_data_length = usize::from(u16::from_le_bytes(self.instruction_data_length));
-                    
-                    if data.len().lt(&(instruction_data_offset + instruction_data_length)){
-                        return Err(ProgramError::InvalidInstructionData);
-                    }
+                DataCommitTypeEnum::Data | DataCommitTypeEnum::Prefix=>{
+                    let commit_instruction_data = sections.instruction_data_ranges.iter().
+                        map(|range| {
+                            let (offset, end) = range.bounds();
 
-                    let commit_instruction_data = [
-                        data[instruction_data_offset..(instruction_data_offset + instruction_data_length)].
-                        as_ref()];
+                            data.get(offset..end).ok_or(ProgramError::InvalidInstructionData)
+                        }).collect::<Result<Vec<&[u8]>, ProgramError>>()?;
                     
-                    hashv(&commit_instruction_data, 
-                    &mut instruction_data_hash);
+                    hashv(&commit_instruction_data, &mut instruction_data_hash);
 
                     if instruction_data_hash != self.instruction_data_hash {
                         return Err(ProgramError::InvalidInstructionData);
@@ -165,31 +1060,37 @@ impl ConfigBase{
                         return Err(ProgramError::InvalidInstructionData);
                     }
                 },
+                DataCommitTypeEnum::Masked => {
+                    if data.len().ne(&usize::from(u16::from_le_bytes(self.instruction_data_length))) || 
+                        !sections.data_matches_mask(data){
+                        return Err(ProgramError::InvalidInstructionData);
+                    }
+                },
                 DataCommitTypeEnum::AnyData => {} // Ok
             }
         }
         None => {}
     };
-
-    sol_log_slice(self.account_indices.as_ref());
     
     sol_log_slice(commit_accounts_hash.as_ref());
     
-    // account_indices,
     // signer_count,
     // accounts_hash,
     // instruction_data_commit_type,
-    // instruction_data_offset,
+    // instruction_data_length,
+    // instruction_data_max_length,
     // data_hash,
+    // constraints_hash,
     // commit_bump
     let commit_seeds:[&[u8];COMMIT_SEEDS_LEN] = [
-                                    self.account_indices.as_ref(),
                                     &self.signer_count, 
                                     commit_accounts_hash.as_ref(),
                                     self.instruction_data_commit_type.commit_type.as_ref(),
-                                    self.instruction_data_offset.as_ref(),
+                                    self.instruction_data_length.as_ref(),
+                                    self.instruction_data_max_length.as_ref(),
                                     self.instruction_data_hash.as_ref(),
-                                    &self.commit_bump
+                                    constraints_hash,
+                                    commit_bump
                                 ];
     
     Ok(commit_seeds)
@@ -197,20 +1098,313 @@ impl ConfigBase{
     }
 }
 
-/// Stores information about the configuration
+impl ConfigBase{
+    pub const LEN:usize = core::mem::size_of::<ConfigBase>();
+}
+
+impl ConfigBase{
+    /// Checks if the option is set, the options set which parts of the state are stored
+    #[inline(always)]
+    pub fn has_option(&self, option:u8)->bool{
+        (self.options[0] & option).ne(&0)
+    }
+
+    /// Checks if only known options are set and the number of sub-condition slots is
+    /// within the limit
+    pub fn is_options_valid(&self)->Result<(), WrapperError>{
+        let known_options = CONFIG_OPTION_USAGE_WINDOW | CONFIG_OPTION_VALUE_BUDGET | 
+            CONFIG_OPTION_CONDITION_ACCOUNT | CONFIG_OPTION_LAMPORTS_OUT;
+
+        if (self.options[0] & !known_options).ne(&0) || 
+            usize::from(self.sub_condition_count[0]).gt(&CONFIG_MAX_SUB_CONDITIONS){
+            return Err(WrapperError::InvalidConfigOptions);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the condition is valid along with the sub-conditions it combines, see
+    /// CommitCondition::is_valid_with for more information, the condition is either the
+    /// commit condition or the one replacing it, the sub-conditions must fit in the slots
+    /// of the commit, the ones not combined by the condition must be zeroed and the usage
+    /// window and condition account key must be stored when they are read
+    pub fn is_condition_valid(&self, condition:&CommitCondition, 
+        sub_conditions:&[CommitCondition])->Result<(), WrapperError>{
+        if sub_conditions.len().gt(&usize::from(self.sub_condition_count[0])){
+            return Err(WrapperError::InvalidCommitCondition);
+        }
+
+        condition.is_valid_with(sub_conditions)?;
+
+        // The sub-conditions that are not combined must be zeroed, as the unused slots are
+        // when the condition is changed
+        if sub_conditions.iter().skip(condition.get_sub_condition_count()).
+            any(|sub_condition| bytemuck::bytes_of(sub_condition).iter().any(|byte| byte.ne(&0))){
+            return Err(WrapperError::InvalidCommitCondition);
+        }
+
+        if (condition.uses_usage_window_with(sub_conditions) && 
+            !self.has_option(CONFIG_OPTION_USAGE_WINDOW)) ||
+            (condition.uses_condition_account_with(sub_conditions) && 
+            !self.has_option(CONFIG_OPTION_CONDITION_ACCOUNT)){
+            return Err(WrapperError::InvalidCommitCondition);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the value budget is valid for the data commit type, the amount must
+    /// be within the committed instruction data length when it is bounded, a budget is
+    /// only stored when it is used so it can not be `NoBudget`
+    pub fn is_value_budget_valid(&self, value_budget:Option<&ValueBudget>)->Result<(), WrapperError>{
+        let Some(value_budget) = value_budget else{
+            return Ok(());
+        };
+
+        // The data the amount is read from is that of a form passed when invoking for
+        // a forms or bundle commit
+        let instruction_data_length = if self.is_forms_commit() || self.is_bundle_commit(){
+            None
+        }
+        else{
+            self.form.get_max_instruction_data_length()?
+        };
+
+        if matches!(value_budget.is_valid(instruction_data_length)?, ValueBudgetTag::NoBudget){
+            return Err(WrapperError::InvalidValueBudget);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the commit is a forms commit, the forms it allows are the leaves of the
+    /// form root and are passed when invoking
+    #[inline(always)]
+    pub fn is_forms_commit(&self)->bool{
+        u16::from_le_bytes(self.form_leaf_count).ne(&0)
+    }
+
+    /// Checks if the forms commit is valid, the form of a forms commit only commits to the
+    /// program signers so all the other sections must be empty
+    pub fn is_forms_commit_valid(&self, sections:&ConfigSections)->Result<(), WrapperError>{
+        if !self.is_forms_commit(){
+            return Ok(());
+        }
+
+        if !self.is_signers_only_form(sections){
+            return Err(WrapperError::InvalidFormsCommit);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the commit is a bundle commit, the forms of the instructions it executes
+    /// are passed when invoking
+    #[inline(always)]
+    pub fn is_bundle_commit(&self)->bool{
+        self.bundle_length[0].ne(&0)
+    }
+
+    /// Checks if the bundle commit is valid, as with a forms commit the form only commits to
+    /// the program signers, the value budget is spent by every instruction in the budget mask
+    /// so it must be set exactly when there is a budget and only for the instructions of the
+    /// bundle
+    pub fn is_bundle_commit_valid(&self, options:&ConfigOptions, sections:&ConfigSections)->Result<(), WrapperError>{
+        if !self.is_bundle_commit(){
+            return Ok(());
+        }
+
+        let budget_mask = options.bundle.ok_or(WrapperError::InvalidBundle)?.budget_mask[0];
+
+        if self.is_forms_commit() || usize::from(self.bundle_length[0]).gt(&MAX_BUNDLE_LENGTH) ||
+            budget_mask.checked_shr(u32::from(self.bundle_length[0])).unwrap_or(0).ne(&0) ||
+            self.has_option(CONFIG_OPTION_VALUE_BUDGET).ne(&budget_mask.ne(&0)) || 
+            !self.is_signers_only_form(sections){
+            return Err(WrapperError::InvalidBundle);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the form only commits to the program signers, it has as many accounts as
+    /// signers, no data and all the other sections are empty
+    fn is_signers_only_form(&self, sections:&ConfigSections)->bool{
+        self.form.account_count.eq(&self.form.signer_count) &&
+            matches!(DataCommitTypeEnum::try_from(self.form.instruction_data_commit_type), 
+                Ok(DataCommitTypeEnum::NoData)) &&
+            sections.account_flags.is_empty() && sections.account_sets.is_empty() &&
+            sections.account_owner_constraints.is_empty() && sections.account_pda_constraints.is_empty() &&
+            sections.instruction_data_ranges.is_empty() && sections.field_constraints.is_empty() &&
+            sections.value_set_constraints.is_empty() && sections.account_data_assertions.is_empty() &&
+            sections.account_invariants.is_empty()
+    }
+
+    /// Gets the length of the usage table of a forms commit, it holds the number of
+    /// times each leaf has been used along with the state of it's condition
+    #[inline(always)]
+    pub fn get_form_usage_len(&self)->usize{
+        usize::from(u16::from_le_bytes(self.form_leaf_count)) * core::mem::size_of::<FormUsage>()
+    }
+
+    /// Gets the length of the usage window, it is only stored with the usage window option
+    #[inline(always)]
+    pub fn get_usage_window_len(&self)->usize{
+        usize::from(self.has_option(CONFIG_OPTION_USAGE_WINDOW)) * core::mem::size_of::<UsageWindow>()
+    }
+
+    /// Gets the hash of the constraints of the form, see CommitForm::get_constraints_hash, the
+    /// form root and leaf count of a forms commit and the bundle of a bundle commit are hashed
+    /// along with it so they are bound to the commit account
+    pub fn get_constraints_hash(&self, options:&ConfigOptions, sections:&ConfigSections, 
+        constraints_hash:&mut [u8; HASH_LENGTH]){
+        self.form.get_constraints_hash(sections, constraints_hash);
+
+        if let Some(form_root) = options.form_root {
+            let form_constraints_hash = *constraints_hash;
+
+            hashv(&[form_constraints_hash.as_ref(), 
+                self.form_leaf_count.as_ref(), 
+                form_root.as_ref()], constraints_hash);
+        }
+
+        if let Some(bundle) = options.bundle {
+            let form_constraints_hash = *constraints_hash;
+
+            hashv(&[form_constraints_hash.as_ref(), 
+                self.bundle_length.as_ref(), 
+                bundle.budget_mask.as_ref(),
+                bundle.hash.as_ref()], constraints_hash);
+        }
+    }
+
+    /// Gets the seeds used to create the commit account, see CommitForm::get_commit_seeds
+    #[inline(always)]
+    pub fn get_commit_seeds<'a, 'b>(&'a self, sections:&ConfigSections, commit_accounts_hash:&'b[u8], 
+        constraints_hash:&'b[u8], maybe_data:Option<&[u8]>)->Result<[&'b[u8]; COMMIT_SEEDS_LEN], ProgramError>
+    where
+        'a:'b,
+    {
+        self.form.get_commit_seeds(sections, commit_accounts_hash, constraints_hash, &self.commit_bump, maybe_data)
+    }
+
+    /// Checks if the commit condition or any of the sub-conditions it combines
+    /// reads the data of the condition account
+    #[inline(always)]
+    pub fn uses_condition_account(&self, sub_conditions:&[CommitCondition]) -> bool {
+        self.condition.uses_condition_account_with(sub_conditions)
+    }
+
+    /// Checks if the commit condition can never be met again, see CommitCondition::is_expired
+    /// and CommitCondition::is_composite_expired for more information
+    pub fn is_condition_expired(&self, sub_conditions:&[CommitCondition])->Result<bool, ProgramError>{
+        let clock = Clock::get()?;
+
+        if self.condition.is_valid()?.is_composite(){
+            return self.condition.is_composite_expired(sub_conditions, &clock);
+        }
+
+        self.condition.is_expired(&clock)
+    }
+
+    /// Updates the commit condition, see CommitCondition::update and
+    /// CommitCondition::update_composite for more information, the condition account
+    /// is expected to have been checked against the condition account key
+    #[inline(always)]
+    pub fn update_condition(&mut self, sub_conditions:&mut [CommitCondition], usage_window:Option<&mut UsageWindow>, 
+        condition_account:Option<&AccountInfo>)->Result<(), ProgramError>{
+        let context = ConditionContext{
+            clock: Clock::get()?,
+            condition_account
+        };
+
+        // The usage window is stored whenever a condition records the recent uses in it,
+        // see ConfigBase::is_condition_valid
+        let mut unused_usage_window = UsageWindow::zeroed();
+
+        let usage_window = usage_window.unwrap_or(&mut unused_usage_window);
+
+        if self.condition.is_valid()?.is_composite(){
+            return self.condition.update_composite(sub_conditions, &context, usage_window);
+        }
+
+        self.condition.update(&context, usage_window)
+    }
+}
+
+/// Stores the form of the instruction that is committed to, the counts set
+/// the lengths of the sections that follow, see ConfigSections for more information
 #[repr(C, packed)]
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
-pub struct ConfigBase{
+pub struct CommitForm{
     pub account_count: [u8;1], 
-    pub account_indices: [u8; CONFIG_MAX_ACCOUNTS],
+    pub account_flag_count: [u8;1],
+    pub other_account_flags: [u8;1],
+    pub account_set_count: [u8;1],
+    pub account_owner_constraint_count: [u8;1],
+    pub account_pda_constraint_count: [u8;1],
     pub signer_count: [u8;1],
-    pub signer_bumps: [u8; CONFIG_MAX_SIGNERS],
     pub instruction_data_commit_type:DataCommitType,
     pub instruction_data_length: [u8;2], 
-    pub instruction_data_offset: [u8; 2],
+    pub instruction_data_max_length: [u8;2],
+    pub instruction_data_range_count: [u8;1],
     pub instruction_data_hash: HashType,
+    pub field_constraint_count: [u8;1],
+    pub value_set_constraint_count: [u8;1],
+    pub account_data_assertion_count: [u8;1],
+    pub account_invariant_count: [u8;1],
+}
+
+/// Stores information about the configuration, the form along with the
+/// commit condition, the sub-condition count is the number of sub-condition
+/// slots and the options set which of the usage window, value budget and
+/// condition account key are stored(see ConfigState) and if the outflow of
+/// the program signers is capped, when the form leaf count is set the commit
+/// is a forms commit and the forms are leaves of the form root, when the bundle
+/// length is set the commit is a bundle commit and the forms of the instructions
+/// executed in order are bound by the bundle hash(see ConfigOptions)
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct ConfigBase{
+    pub form: CommitForm,
     pub commit_bump: [u8;1],
     pub condition: CommitCondition,
+    pub sub_condition_count: [u8;1],
+    pub options: [u8;1],
+    pub form_leaf_count: [u8;2],
+    pub bundle_length: [u8;1],
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gets the sections of a form that only sets the template and mask
+    fn masked_sections<'a>(template:&'a [u8], mask:&'a [u8])->ConfigSections<'a>{
+        ConfigSections{
+            account_indices: &[],
+            account_flags: &[],
+            account_sets: &[],
+            account_set_keys: &[],
+            account_owner_constraints: &[],
+            account_pda_constraints: &[],
+            account_pda_seeds: &[],
+            signer_bumps: &[],
+            instruction_data_ranges: &[],
+            instruction_data_template: template,
+            instruction_data_mask: mask,
+            field_constraints: &[],
+            value_set_constraints: &[],
+            account_data_assertions: &[],
+            account_invariants: &[],
+        }
+    }
+
+    #[test]
+    fn zeroed_mask_is_not_set() {
+        let template = [1, 2, 3, 4];
 
+        assert!(!masked_sections(&template, &[0; 4]).is_data_mask_set());
+
+        assert!(masked_sections(&template, &[0, 0xff, 0, 0]).is_data_mask_set());
+    }
+}
@@ -1,10 +1,21 @@
 pub const MIN_ACCOUNT_COUNT:usize = 2;
 pub const MAX_ACCOUNT_COUNT:usize = 8;
 pub const MIN_NON_SIGNER_ACCOUNT_COUNT:usize = 2;
-pub const MAX_INSTRUCTION_DATA_LENGTH:usize = 64;
 pub const HASH_LENGTH:usize = 32;
-pub const CONFIG_MAX_SIGNERS:usize = 4;
-pub const CONFIG_MAX_ACCOUNTS:usize = 8;
 pub const CONFIG_MIN_ACCOUNTS:usize = 3;
-pub const COMMIT_SEEDS_LEN:usize = 7;
-pub const MAX_INSTRUCTION_ACCOUNTS:usize = 32;
\ No newline at end of file
+pub const COMMIT_SEEDS_LEN:usize = 8;
+pub const CONFIG_MAX_SUB_CONDITIONS:usize = 4;
+pub const SLIDING_WINDOW_MAX_USES:usize = 16;
+pub const VALUE_SET_MAX_VALUES:usize = 8;
+pub const CONFIG_OPTION_USAGE_WINDOW:u8 = 1;
+pub const CONFIG_OPTION_VALUE_BUDGET:u8 = 2;
+pub const CONFIG_OPTION_CONDITION_ACCOUNT:u8 = 4;
+pub const CONFIG_OPTION_LAMPORTS_OUT:u8 = 8;
+pub const ACCOUNT_FLAG_WRITABLE:u8 = 1;
+pub const ACCOUNT_FLAG_SIGNER:u8 = 2;
+pub const MERKLE_LEAF_PREFIX:u8 = 0;
+pub const MERKLE_NODE_PREFIX:u8 = 1;
+pub const ACCOUNT_CHECK_DATA_LENGTH:u8 = 1;
+pub const ACCOUNT_CHECK_DISCRIMINATOR:u8 = 2;
+pub const MAX_PDA_SEEDS:usize = 16;
+pub const MAX_BUNDLE_LENGTH:usize = 4;
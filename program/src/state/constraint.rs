@@ -0,0 +1,195 @@
+use bytemuck::{
+    Pod,
+    Zeroable
+};
+
+use pinocchio::{
+    program_error::ProgramError
+};
+
+use crate::{
+    WrapperError, constants::VALUE_SET_MAX_VALUES
+};
+
+/// Enum for the type of an instruction data field, all the
+/// types are little endian
+/// U8 => 0
+/// U16 => 1
+/// U32 => 2
+/// U64 => 3
+/// I64 => 4
+#[repr(u8)]
+pub enum FieldType {
+    U8 = 0,
+    U16 = 1,
+    U32 = 2,
+    U64 = 3,
+    I64 = 4
+}
+
+impl TryFrom<u8> for FieldType {
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(FieldType::U8),
+            1 => Ok(FieldType::U16),
+            2 => Ok(FieldType::U32),
+            3 => Ok(FieldType::U64),
+            4 => Ok(FieldType::I64),
+            _ => Err(WrapperError::InvalidFieldConstraint),
+        }
+    }
+
+    type Error = WrapperError;
+}
+
+impl FieldType {
+    /// Gets the number of bytes the field takes
+    #[inline(always)]
+    pub fn width(&self) -> usize {
+        match self {
+            FieldType::U8 => 1,
+            FieldType::U16 => 2,
+            FieldType::U32 => 4,
+            FieldType::U64 | FieldType::I64 => 8,
+        }
+    }
+
+    /// Decodes the field from it's bytes, the bytes are zero extended to
+    /// eight bytes so the unsigned types can be read as a u64
+    #[inline(always)]
+    pub fn decode(&self, bytes:&[u8]) -> i128 {
+        let mut value_bytes:[u8;8] = [0;8];
+
+        value_bytes[..bytes.len()].copy_from_slice(bytes);
+
+        match self {
+            FieldType::I64 => i128::from(i64::from_le_bytes(value_bytes)),
+            _ => i128::from(u64::from_le_bytes(value_bytes)),
+        }
+    }
+}
+
+/// Stores the inclusive bounds of a field of the instruction data, the
+/// bounds are stored as eight bytes of the field's type
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct FieldConstraint {
+    pub field_type: u8,
+    pub offset: [u8; 2],
+    pub min: [u8; 8],
+    pub max: [u8; 8],
+}
+
+impl FieldConstraint{
+    pub const LEN:usize = core::mem::size_of::<FieldConstraint>();
+}
+
+impl FieldConstraint{
+
+    /// Checks if the field type is valid, the bounds are ordered and the field is within
+    /// instruction data of the given maximum length when it is bounded
+    pub fn is_valid(&self, instruction_data_length:Option<usize>)->Result<(), WrapperError>{
+        let field_type = FieldType::try_from(self.field_type)?;
+
+        let end = usize::from(u16::from_le_bytes(self.offset)) + field_type.width();
+
+        if let Some(instruction_data_length) = instruction_data_length {
+            if end.gt(&instruction_data_length){
+                return Err(WrapperError::InvalidFieldConstraint);
+            }
+        }
+
+        if field_type.decode(&self.min).gt(&field_type.decode(&self.max)){
+            return Err(WrapperError::InvalidFieldConstraint);
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the field of the instruction data is within the bounds
+    pub fn check(&self, instruction_data:&[u8])->Result<(), ProgramError>{
+        let field_type = FieldType::try_from(self.field_type)?;
+
+        let offset = usize::from(u16::from_le_bytes(self.offset));
+
+        let field_bytes = instruction_data.get(offset..(offset + field_type.width())).
+            ok_or(ProgramError::InvalidInstructionData)?;
+
+        let value = field_type.decode(field_bytes);
+
+        if value.lt(&field_type.decode(&self.min)) || value.gt(&field_type.decode(&self.max)){
+            return Err(WrapperError::FieldOutOfBounds.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Stores the values a field of the instruction data is allowed to take, the field
+/// is compared byte for byte so it can hold any value of up to eight bytes such as
+/// a discriminator, the bytes of each value past the width must be zero
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct ValueSetConstraint {
+    pub offset: [u8; 2],
+    pub width: u8,
+    pub value_count: u8,
+    pub values: [[u8; 8]; VALUE_SET_MAX_VALUES],
+}
+
+impl ValueSetConstraint{
+    pub const LEN:usize = core::mem::size_of::<ValueSetConstraint>();
+}
+
+impl ValueSetConstraint{
+
+    /// Checks if the width and value count are valid, the unused bytes are zero and the
+    /// field is within instruction data of the given maximum length when it is bounded
+    pub fn is_valid(&self, instruction_data_length:Option<usize>)->Result<(), WrapperError>{
+        let width = usize::from(self.width);
+
+        let value_count = usize::from(self.value_count);
+
+        if width.eq(&0) || width.gt(&core::mem::size_of::<u64>()) ||
+            value_count.eq(&0) || value_count.gt(&VALUE_SET_MAX_VALUES){
+            return Err(WrapperError::InvalidFieldConstraint);
+        }
+
+        let end = usize::from(u16::from_le_bytes(self.offset)) + width;
+
+        if let Some(instruction_data_length) = instruction_data_length {
+            if end.gt(&instruction_data_length){
+                return Err(WrapperError::InvalidFieldConstraint);
+            }
+        }
+
+        for (index, value) in self.values.iter().enumerate(){
+            let unused_bytes = if index.lt(&value_count) { &value[width..] } else { &value[..] };
+
+            if unused_bytes.iter().any(|byte| byte.ne(&0)){
+                return Err(WrapperError::InvalidFieldConstraint);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the field of the instruction data is one of the allowed values
+    pub fn check(&self, instruction_data:&[u8])->Result<(), ProgramError>{
+        let width = usize::from(self.width);
+
+        let offset = usize::from(u16::from_le_bytes(self.offset));
+
+        let field_bytes = instruction_data.get(offset..(offset + width)).
+            ok_or(ProgramError::InvalidInstructionData)?;
+
+        let is_allowed = self.values.iter().take(usize::from(self.value_count)).
+            any(|value| value.get(..width).is_some_and(|value| value.eq(field_bytes)));
+
+        if !is_allowed{
+            return Err(WrapperError::FieldValueNotAllowed.into());
+        }
+
+        Ok(())
+    }
+}
@@ -30,7 +30,69 @@ pub enum WrapperError {
 
     InvalidAccountKeysCount,
 
-    InvalidDataCommitType
+    InvalidDataCommitType,
+
+    NegatedConditionMet,
+
+    InvalidComparison,
+
+    AccountConditionNotMet,
+
+    InvalidValueBudget,
+
+    ValueBudgetExceeded,
+
+    CommitNotExpired,
+
+    InvalidRecipient,
+
+    InvalidDataRange,
+
+    InvalidDataMask,
+
+    InvalidFieldConstraint,
+
+    FieldOutOfBounds,
+
+    FieldValueNotAllowed,
+
+    InvalidConfigOptions,
+
+    InvalidAccountFlags,
+
+    AccountFlagsMismatch,
+
+    InvalidAccountSet,
+
+    AccountNotInSet,
+
+    InvalidAccountOwnerConstraint,
+
+    AccountOwnerConstraintNotMet,
+
+    InvalidPdaConstraint,
+
+    PdaConstraintNotMet,
+
+    InvalidFormsCommit,
+
+    InvalidFormLeaf,
+
+    InvalidBundle,
+
+    InvalidBundleInstruction,
+
+    InvalidAccountDataAssertion,
+
+    AccountDataAssertionNotMet,
+
+    InvalidAccountInvariant,
+
+    AccountInvariantNotMet,
+
+    LamportOutflowExceeded,
+
+    InvalidMaxLamportsOut
 }
 
 impl From<WrapperError> for ProgramError {
@@ -0,0 +1,202 @@
+use bytemuck::{
+    Pod,
+    Zeroable
+};
+
+use pinocchio::{
+    program_error::ProgramError
+};
+
+use crate::{
+    WrapperError,
+    condition::{CommitCondition, CommitConditionTag, ConditionContext, UsageWindow},
+    config::{CommitForm, ConfigSections},
+    constants::HASH_LENGTH,
+    utils::{hashv, verify_merkle_proof}
+};
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+/// Stores the number of times a leaf of a forms commit has been used along with the
+/// state of it's condition, the condition is copied from the leaf on it's first use
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct FormUsage {
+    pub uses: [u8; 4],
+    pub condition: CommitCondition,
+}
+
+/// Stores the leaf of a forms commit, it is passed at the end of the Entry instruction
+/// data after the form(the commit form followed by it's sections) and the proof(proof
+/// length hashes), the leaf can be used at most max uses times(zero is unlimited) and
+/// only while it's condition is met(the `Default` tag is no condition)
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct FormLeaf {
+    pub leaf_index: [u8; 2],
+    pub max_uses: [u8; 4],
+    pub condition: CommitCondition,
+    pub form_length: [u8; 2],
+    pub proof_length: u8,
+}
+
+impl FormLeaf{
+    pub const LEN:usize = core::mem::size_of::<FormLeaf>();
+}
+
+/// Stores a form passed in the instruction data, the commit form followed by it's sections
+#[derive(Debug, Clone, Copy)]
+pub struct FormData<'a> {
+    pub form: &'a CommitForm,
+    pub sections: ConfigSections<'a>,
+}
+
+impl<'a> FormData<'a>{
+
+    /// Extracts the form from the data, the data must be exactly as long as the
+    /// counts in the form require and the sections must be valid for the form
+    pub fn try_from_bytes(data:&'a [u8])->Result<Self, ProgramError>{
+        if data.len().lt(&CommitForm::LEN){
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Commit form -- Config sections
+        let (form, sections_data) = data.split_at(CommitForm::LEN);
+
+        let form = bytemuck::try_from_bytes::<CommitForm>(form).
+            map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let (sections, unused_data) = ConfigSections::try_from_bytes(form, sections_data).
+            map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        if unused_data.len().ne(&0){
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // The sections of a form are not part of it's seeds, they are checked as they
+        // are when a commit is created
+        form.is_data_commit_valid(&sections)?;
+        form.is_account_sets_valid(&sections)?;
+        form.is_account_owner_constraints_valid(&sections)?;
+        form.is_account_pda_constraints_valid(&sections)?;
+        form.is_account_data_assertions_valid(&sections)?;
+        form.is_account_invariants_valid(&sections)?;
+        form.is_account_flags_valid(&sections)?;
+        form.is_field_constraints_valid(&sections)?;
+
+        Ok(FormData{ form, sections })
+    }
+
+    /// Checks if the form signs with the program signers of the commit account, the
+    /// signer bumps must be the same as the ones in the sections of the commit account
+    #[inline(always)]
+    pub fn has_signers_of(&self, sections:&ConfigSections)->bool{
+        self.sections.signer_bumps.eq(sections.signer_bumps)
+    }
+}
+
+/// Stores the leaf of a forms commit along with it's form and proof
+#[derive(Debug, Clone, Copy)]
+pub struct FormLeafData<'a> {
+    pub leaf: &'a FormLeaf,
+    pub form: FormData<'a>,
+    pub proof: &'a [u8],
+}
+
+impl<'a> FormLeafData<'a>{
+
+    /// Splits the leaf off the end of the data along with it's proof and form, returns it
+    /// with the rest of the data
+    /// [Rest of the data] -- Form -- Proof -- Leaf
+    pub fn try_split_from(data:&'a [u8])->Result<(Self, &'a [u8]), ProgramError>{
+        let (data, leaf) = Self::split_end(data, FormLeaf::LEN)?;
+
+        let leaf = bytemuck::try_from_bytes::<FormLeaf>(leaf).
+            map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let (data, proof) = Self::split_end(data, usize::from(leaf.proof_length) * HASH_LENGTH)?;
+
+        let (data, form_data) = Self::split_end(data, usize::from(u16::from_le_bytes(leaf.form_length)))?;
+
+        let form = FormData::try_from_bytes(form_data)?;
+
+        Ok((FormLeafData{ leaf, form, proof }, data))
+    }
+
+    /// Splits `length` bytes off the end of the data
+    #[inline(always)]
+    fn split_end(data:&'a [u8], length:usize)->Result<(&'a [u8], &'a [u8]), ProgramError>{
+        if data.len().lt(&length){
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(data.split_at(data.len() - length))
+    }
+
+    /// Checks if the leaf is part of the tree with the given root, the leaf is the hash of
+    /// the leaf index, maximum uses and condition followed by the seeds of the form without
+    /// the bump
+    pub fn is_leaf_of(&self, seeds:&[&[u8]], root:&[u8; HASH_LENGTH])->bool{
+        let mut leaf_values:Vec<&[u8]> = Vec::with_capacity(seeds.len() + 3);
+
+        leaf_values.push(self.leaf.leaf_index.as_ref());
+
+        leaf_values.push(self.leaf.max_uses.as_ref());
+
+        leaf_values.push(bytemuck::bytes_of(&self.leaf.condition));
+
+        leaf_values.extend_from_slice(seeds);
+
+        let mut leaf_hash:[u8;HASH_LENGTH] = [0;HASH_LENGTH];
+
+        hashv(&leaf_values, &mut leaf_hash);
+
+        verify_merkle_proof(&leaf_hash, self.proof, root)
+    }
+
+    /// Records a use of the leaf in the usage table, returns an error if the leaf
+    /// has already been used the maximum number of times or it's condition is not met
+    pub fn update_usage(&self, form_usage:&mut [FormUsage], context:&ConditionContext)->Result<(), ProgramError>{
+        let usage = form_usage.get_mut(usize::from(u16::from_le_bytes(self.leaf.leaf_index))).
+            ok_or(ProgramError::InvalidInstructionData)?;
+
+        let uses = u32::from_le_bytes(usage.uses).checked_add(1).
+            ok_or(ProgramError::ArithmeticOverflow)?;
+
+        let max_uses = u32::from_le_bytes(self.leaf.max_uses);
+
+        if max_uses.ne(&0) && uses.gt(&max_uses){
+            return Err(WrapperError::CountExhausted.into());
+        }
+
+        usage.uses = uses.to_le_bytes();
+
+        self.update_condition(&mut usage.condition, context)
+    }
+
+    /// Checks the condition of the leaf against the context and updates it's state in the
+    /// usage table, the state starts as the condition of the leaf on it's first use, a leaf
+    /// condition can not be composite or read the usage window or the condition account
+    /// as those are kept once for the commit account
+    fn update_condition(&self, condition:&mut CommitCondition, context:&ConditionContext)->Result<(), ProgramError>{
+        let tag = self.leaf.condition.is_valid()?;
+
+        if matches!(tag, CommitConditionTag::Default){
+            return Ok(());
+        }
+
+        if tag.is_composite() || matches!(tag, 
+            CommitConditionTag::SlidingWindowCount | CommitConditionTag::AccountValue){
+            return Err(WrapperError::InvalidFormLeaf.into());
+        }
+
+        if matches!(condition.is_valid()?, CommitConditionTag::Default){
+            *condition = self.leaf.condition;
+        }
+
+        // None of the allowed conditions read the usage window
+        condition.update(context, &mut UsageWindow::zeroed())
+    }
+}
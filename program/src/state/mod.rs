@@ -1,5 +1,10 @@
 pub mod condition;
+pub mod budget;
+pub mod constraint;
+pub mod account_constraint;
 pub mod error;
 pub use error::*;
 pub mod config;
+pub mod form;
+pub mod bundle;
 pub mod constants;